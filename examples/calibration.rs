@@ -2,7 +2,9 @@ use ray_tracer::{
     ball,
     camera::{Camera, CameraParameters, RenderParameters},
     hittable::HittableList,
+    image,
     material::Lambertian,
+    scene::Scene,
     sphere::Sphere,
     v3,
     vec3::Vec3,
@@ -26,10 +28,6 @@ fn main() {
     };
 
     let cam = Camera::new(cparams, rparams);
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .open("calibration.ppm")
-        .expect("open calibration.ppm");
-    cam.render(&mut file, &world);
+    let film = cam.render(&Scene::new(&world));
+    image::save("calibration.png", &film).expect("write calibration.png");
 }
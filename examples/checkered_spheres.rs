@@ -0,0 +1,44 @@
+use ray_tracer::{
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::HittableList,
+    image,
+    material::{Lambertian, Metal},
+    scene::Scene,
+    sphere::Sphere,
+    texture::{Checker, UvChecker},
+    v3,
+    vec3::Vec3,
+};
+
+fn main() {
+    let checker = Checker::obj(0.32, v3!(0.2, 0.3, 0.1), v3!(0.9, 0.9, 0.9));
+    let stripes = UvChecker::obj(16., 8., v3!(0.8, 0.1, 0.1), v3!(0.9, 0.9, 0.9));
+
+    let mut world = HittableList::new();
+    world.add(Sphere::obj(
+        v3!(0, -1000, 0),
+        1000.,
+        Lambertian::obj(checker),
+    ));
+    world.add(Sphere::obj(v3!(-1.1, 1, 0), 1., Lambertian::obj(stripes)));
+    world.add(Sphere::obj(
+        v3!(1.1, 1, 0),
+        1.,
+        Metal::obj(
+            UvChecker::obj(8., 4., v3!(0.9, 0.7, 0.3), v3!(0.6, 0.6, 0.6)),
+            0.1,
+        ),
+    ));
+
+    let rparams = RenderParameters::default();
+    let cparams = CameraParameters {
+        look_from: v3!(0, 2, 7),
+        look_at: v3!(0, 0.8, 0),
+        vfov: 35.,
+        focus_distance: 7.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let film = cam.render(&Scene::new(&world));
+    image::save("checkered_spheres.png", &film).expect("write checkered_spheres.png");
+}
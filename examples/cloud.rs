@@ -0,0 +1,72 @@
+use ray_tracer::{
+    background::Gradient,
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::HittableList,
+    image,
+    material::{DiffuseLight, Lambertian},
+    medium::{DensityGrid, GridMedium},
+    perlin::Perlin,
+    scene::Scene,
+    sphere::Sphere,
+    v3,
+    vec3::Vec3,
+};
+
+/// Usage: cargo run --release --example cloud -- [grid.raw]
+///
+/// Renders a density grid file, or a cloud made from noise when none is
+/// given.
+fn main() {
+    let grid = match std::env::args().nth(1) {
+        Some(path) => DensityGrid::load(&path).expect("load density grid"),
+        None => {
+            let noise = Perlin::new(7);
+            DensityGrid::from_fn([96, 64, 96], |p| {
+                // a squashed ball with a fraying edge
+                let offset = *p - v3!(0.5, 0.45, 0.5);
+                let falloff = 1.0 - (offset * v3!(2.2, 3.2, 2.2)).len();
+                let detail = noise.fbm(&(*p * 6.0), 5, 2.0, 0.5);
+                (falloff + 0.6 * detail).max(0.0) * 10.0
+            })
+        }
+    };
+
+    let mut world = HittableList::new();
+    world.add(Sphere::obj(
+        v3!(0, -1000, 0),
+        1000.,
+        Lambertian::obj(v3!(0.4, 0.45, 0.35)),
+    ));
+    world.add(Sphere::obj(
+        v3!(-30, 60, -40),
+        10.,
+        DiffuseLight::obj(v3!(8, 7, 6)),
+    ));
+    world.add(Box::new(
+        GridMedium::new(
+            grid,
+            v3!(-3, 2, -3),
+            v3!(3, 6, 3),
+            v3!(0.05, 0.05, 0.05),
+            v3!(1.2, 1.2, 1.2),
+        )
+        .with_anisotropy(0.6),
+    ));
+
+    let rparams = RenderParameters {
+        image_width: 600.,
+        samples_per_pixel: 100.,
+        ..Default::default()
+    };
+    let cparams = CameraParameters {
+        look_from: v3!(0, 1.5, 14),
+        look_at: v3!(0, 3.5, 0),
+        vfov: 35.,
+        focus_distance: 14.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let scene = Scene::new(&world).with_background(Gradient::sky());
+    let film = cam.render(&scene);
+    image::save("cloud.png", &film).expect("write cloud.png");
+}
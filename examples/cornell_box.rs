@@ -0,0 +1,84 @@
+use ray_tracer::{
+    background::Solid,
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::{Hit, HittableList},
+    image,
+    instance::Instance,
+    material::{DiffuseLight, Lambertian},
+    math::Transform,
+    quad::{Quad, cuboid},
+    scene::Scene,
+    v3,
+    vec3::Vec3,
+};
+use std::sync::Arc;
+
+fn main() {
+    let red = Lambertian::obj(v3!(0.65, 0.05, 0.05));
+    let white = Lambertian::obj(v3!(0.73, 0.73, 0.73));
+    let green = Lambertian::obj(v3!(0.12, 0.45, 0.15));
+    let light = DiffuseLight::obj(v3!(15, 15, 15));
+
+    let mut world = HittableList::new();
+    world.add(Quad::obj(
+        v3!(555, 0, 0),
+        v3!(0, 555, 0),
+        v3!(0, 0, 555),
+        green,
+    ));
+    world.add(Quad::obj(v3!(0, 0, 0), v3!(0, 555, 0), v3!(0, 0, 555), red));
+    world.add(Quad::obj(
+        v3!(343, 554, 332),
+        v3!(-130, 0, 0),
+        v3!(0, 0, -105),
+        light,
+    ));
+    world.add(Quad::obj(
+        v3!(0, 0, 0),
+        v3!(555, 0, 0),
+        v3!(0, 0, 555),
+        white.clone(),
+    ));
+    world.add(Quad::obj(
+        v3!(555, 555, 555),
+        v3!(-555, 0, 0),
+        v3!(0, 0, -555),
+        white.clone(),
+    ));
+    world.add(Quad::obj(
+        v3!(0, 0, 555),
+        v3!(555, 0, 0),
+        v3!(0, 555, 0),
+        white.clone(),
+    ));
+    let tall: Arc<dyn Hit> = cuboid(v3!(0, 0, 0), v3!(165, 330, 165), white.clone()).into();
+    world.add(Box::new(
+        Instance::new(tall, Transform::identity())
+            .rotate(&v3!(0, 1, 0), 15.)
+            .translate(v3!(265, 0, 295)),
+    ));
+    let short: Arc<dyn Hit> = cuboid(v3!(0, 0, 0), v3!(165, 165, 165), white).into();
+    world.add(Box::new(
+        Instance::new(short, Transform::identity())
+            .rotate(&v3!(0, 1, 0), -18.)
+            .translate(v3!(130, 0, 65)),
+    ));
+
+    let rparams = RenderParameters {
+        image_width: 600.,
+        aspect_ratio: 1.0,
+        samples_per_pixel: 200.,
+        ..Default::default()
+    };
+    let cparams = CameraParameters {
+        look_from: v3!(278, 278, -800),
+        look_at: v3!(278, 278, 0),
+        vfov: 40.,
+        focus_distance: 800.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let scene = Scene::new(&world).with_background(Solid::black());
+    let film = cam.render(&scene);
+    image::save("cornell_box.png", &film).expect("write cornell_box.png");
+}
@@ -0,0 +1,59 @@
+use ray_tracer::{
+    background::Solid,
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::HittableList,
+    image,
+    material::{Dielectric, DiffuseLight, Lambertian},
+    scene::Scene,
+    sphere::Sphere,
+    texture::Checker,
+    v3,
+    vec3::Vec3,
+};
+use std::sync::Arc;
+
+/// A grid of lamps seen through balls of diamond and of strongly dispersive
+/// glass, rendered spectrally so the lamps fan out into rainbows.
+fn main() {
+    let mut world = HittableList::new();
+    world.add(Sphere::obj(
+        v3!(-1.1, 1, 0),
+        1.,
+        Arc::new(Dielectric::diamond()),
+    ));
+    world.add(Sphere::obj(
+        v3!(1.1, 1, 0),
+        1.,
+        Arc::new(Dielectric::cauchy(1.6, 0.04)),
+    ));
+    world.add(Sphere::obj(
+        v3!(0, -1000, 0),
+        1000.,
+        Lambertian::obj(Checker::obj(0.5, v3!(0.1, 0.1, 0.1), v3!(0.5, 0.5, 0.5))),
+    ));
+    let lamp = DiffuseLight::obj(v3!(30, 30, 30));
+    for i in -6..=6 {
+        for j in 0..6 {
+            let center = v3!(i as f64 * 0.6, 0.3 + j as f64 * 0.5, -3);
+            world.add(Sphere::obj(center, 0.05, lamp.clone()));
+        }
+    }
+
+    let rparams = RenderParameters {
+        image_width: 600.,
+        samples_per_pixel: 200.,
+        spectral: true,
+        ..Default::default()
+    };
+    let cparams = CameraParameters {
+        look_from: v3!(0, 1.2, 6),
+        look_at: v3!(0, 1, 0),
+        vfov: 30.,
+        focus_distance: 6.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let scene = Scene::new(&world).with_background(Solid::new(v3!(0.15, 0.15, 0.2)));
+    let film = cam.render(&scene);
+    image::save("dispersion.png", &film).expect("write dispersion.png");
+}
@@ -0,0 +1,55 @@
+use ray_tracer::{
+    camera::{Camera, CameraParameters, RenderParameters},
+    environment::EnvironmentMap,
+    hittable::HittableList,
+    image,
+    material::{Dielectric, Lambertian, Metal},
+    scene::Scene,
+    sphere::Sphere,
+    v3,
+    vec3::Vec3,
+};
+
+/// Usage: cargo run --release --example environment -- probe.hdr [rotation]
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        eprintln!("usage: environment <equirectangular .hdr or .exr> [rotation degrees]");
+        std::process::exit(1);
+    };
+    let rotation = args.next().map_or(0.0, |r| r.parse().expect("rotation"));
+    let environment = EnvironmentMap::load(&path)
+        .expect("load environment map")
+        .with_rotation(rotation);
+
+    let mut world = HittableList::new();
+    world.add(Sphere::obj(
+        v3!(0, -1000, 0),
+        1000.,
+        Lambertian::obj(v3!(0.5, 0.5, 0.5)),
+    ));
+    world.add(Sphere::obj(
+        v3!(-2.2, 1, 0),
+        1.,
+        Lambertian::obj(v3!(0.7, 0.3, 0.2)),
+    ));
+    world.add(Sphere::obj(v3!(0, 1, 0), 1., Dielectric::obj(1.5)));
+    world.add(Sphere::obj(
+        v3!(2.2, 1, 0),
+        1.,
+        Metal::obj(v3!(0.9, 0.9, 0.9), 0.0),
+    ));
+
+    let rparams = RenderParameters::default();
+    let cparams = CameraParameters {
+        look_from: v3!(0, 2, 9),
+        look_at: v3!(0, 1, 0),
+        vfov: 35.,
+        focus_distance: 9.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let film = cam.render(&Scene::new(&world).with_background(environment));
+    image::save("environment.exr", &film).expect("write environment.exr");
+    image::save("environment.png", &film).expect("write environment.png");
+}
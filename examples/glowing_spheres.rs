@@ -0,0 +1,51 @@
+use ray_tracer::{
+    background::Solid,
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::HittableList,
+    image,
+    material::{DiffuseLight, Lambertian, Metal},
+    scene::Scene,
+    sphere::Sphere,
+    v3,
+    vec3::Vec3,
+};
+
+fn main() {
+    let mut world = HittableList::new();
+    world.add(Sphere::obj(
+        v3!(0, -1000, 0),
+        1000.,
+        Lambertian::obj(v3!(0.5, 0.5, 0.5)),
+    ));
+    world.add(Sphere::obj(
+        v3!(-1.2, 1, 0),
+        1.,
+        Lambertian::obj(v3!(0.8, 0.3, 0.2)),
+    ));
+    world.add(Sphere::obj(
+        v3!(1.2, 1, 0),
+        1.,
+        Metal::obj(v3!(0.8, 0.8, 0.8), 0.05),
+    ));
+    world.add(Sphere::obj(
+        v3!(0, 3.5, 1),
+        0.75,
+        DiffuseLight::obj(v3!(8, 7, 6)),
+    ));
+
+    let rparams = RenderParameters {
+        samples_per_pixel: 400.,
+        ..Default::default()
+    };
+    let cparams = CameraParameters {
+        look_from: v3!(0, 2.5, 8),
+        look_at: v3!(0, 1, 0),
+        vfov: 35.,
+        focus_distance: 8.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let scene = Scene::new(&world).with_background(Solid::black());
+    let film = cam.render(&scene);
+    image::save("glowing_spheres.png", &film).expect("write glowing_spheres.png");
+}
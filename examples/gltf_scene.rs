@@ -0,0 +1,39 @@
+use ray_tracer::{
+    camera::{Camera, CameraParameters, RenderParameters},
+    gltf::load_gltf,
+    hittable::Hit,
+    image,
+    scene::Scene,
+    v3,
+    vec3::Vec3,
+};
+
+/// Usage: cargo run --release --example gltf_scene -- scene.glb
+fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: gltf_scene <gltf or glb file>");
+        std::process::exit(1);
+    };
+    let scene = load_gltf(&path).expect("load scene");
+    let world = &scene.world;
+
+    let rparams = RenderParameters {
+        aspect_ratio: scene.aspect_ratio.unwrap_or(16.0 / 9.0),
+        ..Default::default()
+    };
+    // without a camera in the file, look at everything from the front
+    let cparams = scene.camera.unwrap_or_else(|| {
+        let bbox = world.bounding_box();
+        let center = bbox.centroid();
+        let look_from = center + bbox.extent().len() * v3!(0, 0.3, 1.2);
+        CameraParameters {
+            look_from,
+            look_at: center,
+            vfov: 40.,
+            ..Default::default()
+        }
+    });
+    let cam = Camera::new(cparams, rparams);
+    let film = cam.render(&Scene::new(world));
+    image::save("gltf_scene.png", &film).expect("write gltf_scene.png");
+}
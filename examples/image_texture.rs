@@ -0,0 +1,36 @@
+use ray_tracer::{
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::HittableList,
+    image,
+    material::Lambertian,
+    scene::Scene,
+    sphere::Sphere,
+    texture::{ImageTexture, Texture},
+    v3,
+    vec3::Vec3,
+};
+use std::sync::Arc;
+
+/// Usage: cargo run --release --example image_texture -- earthmap.jpg
+fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: image_texture <png, jpeg, ppm, hdr or exr image>");
+        std::process::exit(1);
+    };
+    let texture: Arc<dyn Texture> = Arc::new(ImageTexture::load(&path).expect("load texture"));
+
+    let mut world = HittableList::new();
+    world.add(Sphere::obj(v3!(0, 0, 0), 2., Lambertian::obj(texture)));
+
+    let rparams = RenderParameters::default();
+    let cparams = CameraParameters {
+        look_from: v3!(0, 0, 12),
+        look_at: v3!(0, 0, 0),
+        vfov: 20.,
+        focus_distance: 12.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let film = cam.render(&Scene::new(&world));
+    image::save("image_texture.png", &film).expect("write image_texture.png");
+}
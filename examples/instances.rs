@@ -0,0 +1,61 @@
+use ray_tracer::{
+    background::Gradient,
+    bvh::Bvh,
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::{Hit, HittableList},
+    image,
+    instance::Instance,
+    material::{Lambertian, Metal},
+    math::{Transform, random, random_f64},
+    quad::cuboid,
+    scene::Scene,
+    sphere::Sphere,
+    v3,
+    vec3::Vec3,
+};
+use std::sync::Arc;
+
+/// A field of thousands of tumbling blocks that all share one cuboid.
+fn main() {
+    let gold = Metal::obj(v3!(0.8, 0.6, 0.2), 0.3);
+    let block: Arc<dyn Hit> = cuboid(v3!(-0.5, -0.5, -0.5), v3!(0.5, 0.5, 0.5), gold).into();
+
+    let mut blocks = HittableList::new();
+    for i in -40..40 {
+        for j in -40..10 {
+            let size = random_f64(0.2, 0.6);
+            let axis = v3!(random() - 0.5, random() - 0.5, random() - 0.5);
+            let center = v3!(i as f64 + random_f64(0., 0.5), size / 2., j as f64);
+            blocks.add(Box::new(
+                Instance::new(Arc::clone(&block), Transform::scale(v3!(size, size, size)))
+                    .rotate(&axis, random_f64(0., 360.))
+                    .translate(center),
+            ));
+        }
+    }
+
+    let mut world = HittableList::new();
+    world.add(Bvh::obj(blocks));
+    world.add(Sphere::obj(
+        v3!(0, -1000, 0),
+        1000.,
+        Lambertian::obj(v3!(0.5, 0.5, 0.5)),
+    ));
+
+    let rparams = RenderParameters {
+        image_width: 600.,
+        samples_per_pixel: 50.,
+        ..Default::default()
+    };
+    let cparams = CameraParameters {
+        look_from: v3!(0, 6, 12),
+        look_at: v3!(0, 0, 0),
+        vfov: 40.,
+        focus_distance: 13.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let scene = Scene::new(&world).with_background(Gradient::sky());
+    let film = cam.render(&scene);
+    image::save("instances.png", &film).expect("write instances.png");
+}
@@ -0,0 +1,57 @@
+use ray_tracer::{
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::{Hit, HittableList},
+    image,
+    material::Lambertian,
+    mesh::{load_obj, load_ply, load_stl},
+    scene::Scene,
+    sphere::Sphere,
+    v3,
+    vec3::Vec3,
+};
+
+/// Usage: cargo run --release --example mesh_model -- model.obj
+fn main() {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: mesh_model <obj, ply or stl file>");
+        std::process::exit(1);
+    };
+    let clay = Lambertian::obj(v3!(0.7, 0.6, 0.5));
+    let mesh = match path
+        .rsplit('.')
+        .next()
+        .map(str::to_ascii_lowercase)
+        .as_deref()
+    {
+        Some("ply") => load_ply(&path, clay),
+        Some("stl") => load_stl(&path, clay),
+        _ => load_obj(&path),
+    }
+    .expect("load model");
+    let bbox = mesh.bounding_box();
+    let center = bbox.centroid();
+    let size = bbox.extent().len();
+    println!("{} triangles", mesh.len());
+
+    let mut world = HittableList::new();
+    world.add(Box::new(mesh));
+    // ground plane just under the model
+    world.add(Sphere::obj(
+        Vec3(center.0, bbox.y.min - 1000. * size, center.2),
+        1000. * size,
+        Lambertian::obj(v3!(0.5, 0.5, 0.5)),
+    ));
+
+    let rparams = RenderParameters::default();
+    let look_from = center + size * v3!(0.6, 0.5, 1.2);
+    let cparams = CameraParameters {
+        look_from,
+        look_at: center,
+        vfov: 35.,
+        focus_distance: (look_from - center).len(),
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let film = cam.render(&Scene::new(&world));
+    image::save("mesh_model.png", &film).expect("write mesh_model.png");
+}
@@ -0,0 +1,51 @@
+use ray_tracer::{
+    background::Gradient,
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::HittableList,
+    image,
+    material::{Lambertian, Metal},
+    scene::Scene,
+    sphere::Sphere,
+    texture::Checker,
+    v3,
+    vec3::Vec3,
+};
+use std::sync::Arc;
+
+/// Gold, silver, copper, aluminum, chromium and titanium balls, each whiter
+/// toward its rim where light grazes the surface.
+fn main() {
+    let floor = Lambertian::obj(Checker::obj(0.5, v3!(0.2, 0.2, 0.2), v3!(0.9, 0.9, 0.9)));
+
+    let mut world = HittableList::new();
+    world.add(Sphere::obj(v3!(0, -1000, 0), 1000., floor));
+    let metals = [
+        Metal::gold(0.0),
+        Metal::silver(0.0),
+        Metal::copper(0.0),
+        Metal::aluminum(0.05),
+        Metal::chromium(0.0),
+        Metal::titanium(0.1),
+    ];
+    for (i, metal) in metals.into_iter().enumerate() {
+        let center = v3!(-3.75 + 1.5 * i as f64, 0.65, 0);
+        world.add(Sphere::obj(center, 0.65, Arc::new(metal)));
+    }
+
+    let rparams = RenderParameters {
+        image_width: 600.,
+        samples_per_pixel: 100.,
+        ..Default::default()
+    };
+    let cparams = CameraParameters {
+        look_from: v3!(0, 2, 10),
+        look_at: v3!(0, 0.6, 0),
+        vfov: 30.,
+        focus_distance: 10.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let scene = Scene::new(&world).with_background(Gradient::sky());
+    let film = cam.render(&scene);
+    image::save("metals.png", &film).expect("write metals.png");
+}
@@ -0,0 +1,58 @@
+use ray_tracer::{
+    background::Gradient,
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::{Hit, HittableList},
+    image,
+    instance::Instance,
+    material::{Lambertian, Metal},
+    math::{AnimatedTransform, Transform},
+    quad::cuboid,
+    scene::Scene,
+    sphere::Sphere,
+    v3,
+    vec3::Vec3,
+};
+use std::sync::Arc;
+
+/// A ball dropping and a block spinning while the shutter is open.
+fn main() {
+    let ground = Lambertian::obj(v3!(0.5, 0.5, 0.5));
+    let red = Lambertian::obj(v3!(0.7, 0.1, 0.1));
+    let steel = Metal::obj(v3!(0.7, 0.7, 0.8), 0.1);
+
+    let mut world = HittableList::new();
+    world.add(Sphere::obj(v3!(0, -1000, 0), 1000., ground));
+    world.add(Box::new(
+        Sphere::new(v3!(-1.2, 1.6, 0), 0.5, red).with_motion(v3!(-1.2, 0.5, 0), 0.0, 1.0),
+    ));
+
+    let block: Arc<dyn Hit> = cuboid(v3!(-0.5, -0.5, -0.5), v3!(0.5, 0.5, 0.5), steel).into();
+    let up = v3!(0, 1, 0);
+    let spin = AnimatedTransform::new(&[
+        (0.0, Transform::identity()),
+        (0.5, Transform::rotate(&up, 30.)),
+        (1.0, Transform::rotate(&up, 60.)),
+    ]);
+    world.add(Box::new(
+        Instance::animated(block, spin).translate(v3!(1.2, 0.5, 0)),
+    ));
+
+    let rparams = RenderParameters {
+        image_width: 600.,
+        samples_per_pixel: 100.,
+        ..Default::default()
+    };
+    let cparams = CameraParameters {
+        look_from: v3!(0, 2, 6),
+        look_at: v3!(0, 0.6, 0),
+        vfov: 30.,
+        focus_distance: 6.,
+        shutter_open: 0.0,
+        shutter_close: 1.0,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let scene = Scene::new(&world).with_background(Gradient::sky());
+    let film = cam.render(&scene);
+    image::save("motion_blur.png", &film).expect("write motion_blur.png");
+}
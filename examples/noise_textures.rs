@@ -0,0 +1,50 @@
+use ray_tracer::{
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::HittableList,
+    image,
+    material::Lambertian,
+    scene::Scene,
+    sphere::Sphere,
+    texture::{Marble, Texture, Turbulence, Wood},
+    v3,
+    vec3::Vec3,
+};
+use std::sync::Arc;
+
+fn main() {
+    let mut world = HittableList::new();
+    world.add(Sphere::obj(
+        v3!(0, -1000, 0),
+        1000.,
+        Lambertian::obj(Turbulence::obj(1, 2.)),
+    ));
+    world.add(Sphere::obj(
+        v3!(-2.2, 1, 0),
+        1.,
+        Lambertian::obj(Marble::obj(2, 4.)),
+    ));
+    world.add(Sphere::obj(
+        v3!(0, 1, 0),
+        1.,
+        Lambertian::obj(Wood::obj(3, 6.)),
+    ));
+    world.add(Sphere::obj(
+        v3!(2.2, 1, 0),
+        1.,
+        Lambertian::obj(
+            Arc::new(Turbulence::new(4, 3.).with_color(v3!(0.2, 0.5, 0.9))) as Arc<dyn Texture>,
+        ),
+    ));
+
+    let rparams = RenderParameters::default();
+    let cparams = CameraParameters {
+        look_from: v3!(0, 2, 9),
+        look_at: v3!(0, 0.8, 0),
+        vfov: 35.,
+        focus_distance: 9.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let film = cam.render(&Scene::new(&world));
+    image::save("noise_textures.png", &film).expect("write noise_textures.png");
+}
@@ -1,9 +1,12 @@
 use ray_tracer::{
     ball,
+    bvh::Bvh,
     camera::{Camera, CameraParameters, RenderParameters},
     hittable::HittableList,
+    image,
     material::{Dielectric, Lambertian, Metal},
     math::{random, random_f64},
+    scene::Scene,
     sphere::Sphere,
     v3,
     vec3::Vec3,
@@ -50,6 +53,7 @@ fn main() {
         image_width: 1200.,
         samples_per_pixel: 500.,
         max_bounces: 50.,
+        ..Default::default()
     };
     let camera_params = CameraParameters {
         vfov: 20.,
@@ -58,12 +62,10 @@ fn main() {
         defocus_angle: 0.6,
         focus_distance: 10.,
         focal_length: 1.0,
+        ..Default::default()
     };
+    let world = Bvh::new(world);
     let camera = Camera::new(camera_params, render_params);
-    let mut output_file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .open("random_spheres.ppm")
-        .expect("random_spheres.ppm");
-    camera.render(&mut output_file, &world);
+    let film = camera.render(&Scene::new(world));
+    image::save("random_spheres.png", &film).expect("write random_spheres.png");
 }
@@ -1,7 +1,9 @@
 use ray_tracer::{
     camera::{Camera, CameraParameters, RenderParameters},
     hittable::HittableList,
+    image,
     material::{Dielectric, Lambertian, Metal},
+    scene::Scene,
     sphere::Sphere,
     v3,
     vec3::Vec3,
@@ -32,10 +34,6 @@ fn main() {
         ..Default::default()
     };
     let cam = Camera::new(cparams, rparams);
-    let mut output_file = std::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .open("three_spheres.ppm")
-        .expect("three_spheres.ppm");
-    cam.render(&mut output_file, &world);
+    let film = cam.render(&Scene::new(&world));
+    image::save("three_spheres.png", &film).expect("write three_spheres.png");
 }
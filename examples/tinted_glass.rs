@@ -0,0 +1,42 @@
+use ray_tracer::{
+    background::Gradient,
+    camera::{Camera, CameraParameters, RenderParameters},
+    hittable::HittableList,
+    image,
+    material::{Dielectric, Lambertian},
+    scene::Scene,
+    sphere::Sphere,
+    texture::Checker,
+    v3,
+    vec3::Vec3,
+};
+use std::sync::Arc;
+
+/// Balls of the same green glass: the thicker the ball, the deeper the tint.
+fn main() {
+    let glass = Arc::new(Dielectric::new(1.5).with_color_at_distance(v3!(0.3, 0.8, 0.4), 1.0));
+    let floor = Lambertian::obj(Checker::obj(0.5, v3!(0.2, 0.2, 0.2), v3!(0.9, 0.9, 0.9)));
+
+    let mut world = HittableList::new();
+    world.add(Sphere::obj(v3!(0, -1000, 0), 1000., floor));
+    world.add(Sphere::obj(v3!(-2.2, 0.3, 0), 0.3, glass.clone()));
+    world.add(Sphere::obj(v3!(-1.0, 0.6, 0), 0.6, glass.clone()));
+    world.add(Sphere::obj(v3!(1.2, 1.2, 0), 1.2, glass));
+
+    let rparams = RenderParameters {
+        image_width: 600.,
+        samples_per_pixel: 100.,
+        ..Default::default()
+    };
+    let cparams = CameraParameters {
+        look_from: v3!(0, 2, 8),
+        look_at: v3!(-0.2, 0.7, 0),
+        vfov: 30.,
+        focus_distance: 8.,
+        ..Default::default()
+    };
+    let cam = Camera::new(cparams, rparams);
+    let scene = Scene::new(&world).with_background(Gradient::sky());
+    let film = cam.render(&scene);
+    image::save("tinted_glass.png", &film).expect("write tinted_glass.png");
+}
@@ -0,0 +1,241 @@
+use crate::interval::{Interval, UNIVERSE_INTERVAL};
+use crate::ray::Ray;
+use crate::vec3::{Point, Vec3};
+
+/// Boxes are never thinner than this along any axis, so flat primitives
+/// still have a box the slab test can hit.
+const MIN_THICKNESS: f64 = 0.0001;
+
+/// Axis aligned bounding box stored as one interval per axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub x: Interval,
+    pub y: Interval,
+    pub z: Interval,
+}
+
+impl Aabb {
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        Self { x, y, z }.padded(MIN_THICKNESS)
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            x: Interval::empty(),
+            y: Interval::empty(),
+            z: Interval::empty(),
+        }
+    }
+
+    pub fn universe() -> Self {
+        Self {
+            x: UNIVERSE_INTERVAL,
+            y: UNIVERSE_INTERVAL,
+            z: UNIVERSE_INTERVAL,
+        }
+    }
+
+    /// Box with `a` and `b` as opposite corners, in any order.
+    pub fn from_points(a: Point, b: Point) -> Self {
+        Self::new(
+            Interval::new(a.0.min(b.0), a.0.max(b.0)),
+            Interval::new(a.1.min(b.1), a.1.max(b.1)),
+            Interval::new(a.2.min(b.2), a.2.max(b.2)),
+        )
+    }
+
+    /// Grows the box just enough to contain `p`. Unlike `from_points` this
+    /// does not pad, so the result can be flat.
+    pub fn enclose(&self, p: Point) -> Self {
+        Self {
+            x: Interval::new(self.x.min.min(p.0), self.x.max.max(p.0)),
+            y: Interval::new(self.y.min.min(p.1), self.y.max.max(p.1)),
+            z: Interval::new(self.z.min.min(p.2), self.z.max.max(p.2)),
+        }
+    }
+
+    /// Widens every axis narrower than `min_size` to exactly `min_size`.
+    pub fn padded(&self, min_size: f64) -> Self {
+        let pad = |ax: &Interval| {
+            if ax.size() < min_size {
+                ax.expand(min_size - ax.size())
+            } else {
+                *ax
+            }
+        };
+        Self {
+            x: pad(&self.x),
+            y: pad(&self.y),
+            z: pad(&self.z),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.size() < 0.0 || self.y.size() < 0.0 || self.z.size() < 0.0
+    }
+
+    pub fn min(&self) -> Point {
+        Vec3(self.x.min, self.y.min, self.z.min)
+    }
+
+    pub fn max(&self) -> Point {
+        Vec3(self.x.max, self.y.max, self.z.max)
+    }
+
+    pub fn extent(&self) -> Vec3 {
+        Vec3(self.x.size(), self.y.size(), self.z.size())
+    }
+
+    /// The smallest box containing both `a` and `b`.
+    pub fn union(a: &Aabb, b: &Aabb) -> Self {
+        Self {
+            x: Interval::enclosing(&a.x, &b.x),
+            y: Interval::enclosing(&a.y, &b.y),
+            z: Interval::enclosing(&a.z, &b.z),
+        }
+    }
+
+    /// interval by index: 0 => x, 1 => y, 2 => z
+    pub fn axis_interval(&self, n: usize) -> &Interval {
+        match n {
+            0 => &self.x,
+            1 => &self.y,
+            _ => &self.z,
+        }
+    }
+
+    /// index of the widest axis, for picking split directions
+    pub fn longest_axis(&self) -> usize {
+        let (dx, dy, dz) = (self.x.size(), self.y.size(), self.z.size());
+        if dx > dy && dx > dz {
+            0
+        } else if dy > dz {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn centroid(&self) -> Point {
+        Vec3(
+            0.5 * (self.x.min + self.x.max),
+            0.5 * (self.y.min + self.y.max),
+            0.5 * (self.z.min + self.z.max),
+        )
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let (dx, dy, dz) = (self.x.size(), self.y.size(), self.z.size());
+        2.0 * (dx * dy + dy * dz + dz * dx)
+    }
+
+    /// Slab test against the ray, restricted to `ray_t`.
+    pub fn hit(&self, r: &Ray, ray_t: &Interval) -> bool {
+        let inv_direction = Vec3(
+            1.0 / r.direction.0,
+            1.0 / r.direction.1,
+            1.0 / r.direction.2,
+        );
+        self.hit_inverse(&r.origin, &inv_direction, ray_t)
+    }
+
+    /// Slab test with a precomputed reciprocal direction, for traversals
+    /// that test the same ray against many boxes.
+    pub(crate) fn hit_inverse(
+        &self,
+        origin: &Point,
+        inv_direction: &Vec3,
+        ray_t: &Interval,
+    ) -> bool {
+        self.clip_inverse(origin, inv_direction, ray_t).is_some()
+    }
+
+    /// The part of `ray_t` during which the ray is inside the box.
+    pub fn clip(&self, r: &Ray, ray_t: &Interval) -> Option<Interval> {
+        let inv_direction = Vec3(
+            1.0 / r.direction.0,
+            1.0 / r.direction.1,
+            1.0 / r.direction.2,
+        );
+        self.clip_inverse(&r.origin, &inv_direction, ray_t)
+    }
+
+    fn clip_inverse(
+        &self,
+        origin: &Point,
+        inv_direction: &Vec3,
+        ray_t: &Interval,
+    ) -> Option<Interval> {
+        let mut t_min = ray_t.min;
+        let mut t_max = ray_t.max;
+        for axis in 0..3 {
+            let ax = self.axis_interval(axis);
+            let o = origin.axis(axis);
+            let inv = inv_direction.axis(axis);
+            let t0 = (ax.min - o) * inv;
+            let t1 = (ax.max - o) * inv;
+            let (t0, t1) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            // written so a NaN from 0 * inf leaves the bound untouched
+            if t0 > t_min {
+                t_min = t0;
+            }
+            if t1 < t_max {
+                t_max = t1;
+            }
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        Some(Interval::new(t_min, t_max))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_aabb_hit() {
+    let bbox = Aabb::from_points(Vec3(-1., -1., -1.), Vec3(1., 1., 1.));
+    let toward = Ray {
+        origin: Vec3(0., 0., -5.),
+        direction: Vec3(0., 0., 1.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let away = Ray {
+        origin: Vec3(0., 0., -5.),
+        direction: Vec3(0., 0., -1.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let beside = Ray {
+        origin: Vec3(2., 0., -5.),
+        direction: Vec3(0., 0., 1.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let ray_t = Interval::new(0.001, f64::MAX);
+    assert!(bbox.hit(&toward, &ray_t));
+    assert!(!bbox.hit(&away, &ray_t));
+    assert!(!bbox.hit(&beside, &ray_t));
+    assert!(!bbox.hit(&toward, &Interval::new(0.001, 3.0)));
+}
+
+#[cfg(test)]
+#[test]
+fn test_aabb_union_and_padding() {
+    let a = Aabb::from_points(Vec3(0., 0., 0.), Vec3(1., 2., 0.));
+    let b = Aabb::from_points(Vec3(-1., 0.5, -3.), Vec3(0.5, 1., -2.));
+    // the flat z axis of `a` is padded around its plane
+    assert!(a.z.size() > 0.0);
+    assert!(a.z.contains(0.0));
+
+    let u = Aabb::union(&a, &b);
+    assert_eq!(u.x, Interval::new(-1., 1.));
+    assert_eq!(u.y, Interval::new(0., 2.));
+    assert_eq!(u.longest_axis(), 2);
+    assert_eq!(Aabb::union(&Aabb::empty(), &a), a);
+    assert!(Aabb::empty().is_empty());
+    assert_eq!(Aabb::empty().surface_area(), 0.0);
+}
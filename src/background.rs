@@ -0,0 +1,77 @@
+use crate::ray::Ray;
+use crate::vec3::{Color, Vec3, dot, unit_vector};
+
+/// Radiance seen by rays that leave the scene without hitting anything.
+pub trait Background: Send + Sync {
+    fn color(&self, r: &Ray) -> Color;
+
+    /// Direction toward the background picked in proportion to its
+    /// brightness, or `None` for backgrounds not worth importance sampling.
+    fn sample(&self) -> Option<Vec3> {
+        None
+    }
+
+    /// Solid angle density with which `sample` picks `direction`.
+    fn pdf(&self, _direction: &Vec3) -> f64 {
+        0.0
+    }
+}
+
+/// Same color in every direction. Black gives a scene lit only by its
+/// emitters.
+pub struct Solid {
+    pub color: Color,
+}
+
+impl Solid {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+
+    pub fn black() -> Self {
+        Self::new(Color::zero())
+    }
+}
+
+impl Background for Solid {
+    fn color(&self, _: &Ray) -> Color {
+        self.color
+    }
+}
+
+/// Linear blend from `bottom` to `top` as the ray direction swings from
+/// `-up` to `up`.
+pub struct Gradient {
+    pub bottom: Color,
+    pub top: Color,
+    pub up: Vec3,
+}
+
+impl Gradient {
+    pub fn new(bottom: Color, top: Color, up: Vec3) -> Self {
+        Self {
+            bottom,
+            top,
+            up: unit_vector(&up),
+        }
+    }
+
+    /// white horizon fading to light blue overhead
+    pub fn sky() -> Self {
+        Self::new(Color::one(), Vec3(0.5, 0.7, 1.0), Vec3(0., 1., 0.))
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self::sky()
+    }
+}
+
+impl Background for Gradient {
+    fn color(&self, r: &Ray) -> Color {
+        let unit_direction = unit_vector(&r.direction);
+        let a = 0.5 * (dot(&unit_direction, &self.up) + 1.0);
+        (1.0 - a) * self.bottom + a * self.top
+    }
+}
@@ -0,0 +1,305 @@
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, HitRecord, HittableList};
+use crate::interval::Interval;
+use crate::ray::Ray;
+use crate::vec3::{Point, Vec3};
+
+/// number of centroid buckets evaluated per axis when looking for a split
+const BUCKETS: usize = 12;
+/// leaves at or below this size are kept even when a split looks cheaper
+const MAX_LEAF_SIZE: usize = 4;
+/// cost of a box test relative to a primitive test
+const TRAVERSAL_COST: f64 = 0.125;
+
+enum NodeKind {
+    Leaf { start: usize, count: usize },
+    // the first child always directly follows its parent
+    Interior { second: usize, axis: usize },
+}
+
+struct Node {
+    bbox: Aabb,
+    kind: NodeKind,
+}
+
+/// Flattened bounding volume hierarchy over a set of primitive bounds.
+///
+/// The tree only knows primitives by index; callers keep the primitives and
+/// supply the per-primitive intersection to `hit`.
+pub(crate) struct BvhTree {
+    nodes: Vec<Node>,
+    order: Vec<usize>,
+}
+
+impl BvhTree {
+    pub(crate) fn build(boxes: &[Aabb]) -> Self {
+        let centroids: Vec<Point> = boxes.iter().map(Aabb::centroid).collect();
+        let mut order: Vec<usize> = (0..boxes.len()).collect();
+        let mut nodes = Vec::with_capacity(2 * boxes.len());
+        if !boxes.is_empty() {
+            Self::build_node(boxes, &centroids, &mut order, 0, &mut nodes);
+        }
+        Self { nodes, order }
+    }
+
+    pub(crate) fn bounding_box(&self) -> Aabb {
+        self.nodes.first().map_or(Aabb::empty(), |node| node.bbox)
+    }
+
+    fn build_node(
+        boxes: &[Aabb],
+        centroids: &[Point],
+        order: &mut [usize],
+        offset: usize,
+        nodes: &mut Vec<Node>,
+    ) -> usize {
+        let bbox = order
+            .iter()
+            .fold(Aabb::empty(), |bbox, &i| Aabb::union(&bbox, &boxes[i]));
+        let index = nodes.len();
+        nodes.push(Node {
+            bbox,
+            kind: NodeKind::Leaf {
+                start: offset,
+                count: order.len(),
+            },
+        });
+        if order.len() == 1 {
+            return index;
+        }
+
+        let centroid_bounds = order
+            .iter()
+            .fold(Aabb::empty(), |bounds, &i| bounds.enclose(centroids[i]));
+        let (axis, mid) = match Self::find_split(boxes, centroids, order, &bbox, &centroid_bounds) {
+            Some((axis, bucket)) => {
+                let ax = centroid_bounds.axis_interval(axis);
+                let mid = partition(order, |i| {
+                    bucket_of(centroids[i].axis(axis), ax.min, ax.size()) <= bucket
+                });
+                (axis, mid)
+            }
+            None if order.len() <= MAX_LEAF_SIZE => return index,
+            // every centroid coincides or no split beats a leaf, but the
+            // leaf is too large: split by count along the widest axis
+            None => {
+                let axis = centroid_bounds.longest_axis();
+                let mid = order.len() / 2;
+                order.select_nth_unstable_by(mid, |&a, &b| {
+                    centroids[a].axis(axis).total_cmp(&centroids[b].axis(axis))
+                });
+                (axis, mid)
+            }
+        };
+
+        let (left, right) = order.split_at_mut(mid);
+        Self::build_node(boxes, centroids, left, offset, nodes);
+        let second = Self::build_node(boxes, centroids, right, offset + mid, nodes);
+        nodes[index].kind = NodeKind::Interior { second, axis };
+        index
+    }
+
+    /// Surface area heuristic over bucketed centroids. Returns the axis and
+    /// the last bucket of the left side, or `None` when a leaf is cheaper.
+    fn find_split(
+        boxes: &[Aabb],
+        centroids: &[Point],
+        order: &[usize],
+        bbox: &Aabb,
+        centroid_bounds: &Aabb,
+    ) -> Option<(usize, usize)> {
+        let parent_area = bbox.surface_area();
+        let leaf_cost = order.len() as f64;
+        let mut best: Option<(usize, usize)> = None;
+        let mut best_cost = f64::MAX;
+
+        for axis in 0..3 {
+            let ax = centroid_bounds.axis_interval(axis);
+            if ax.size() <= 0.0 {
+                continue;
+            }
+            let mut counts = [0usize; BUCKETS];
+            let mut bounds = [Aabb::empty(); BUCKETS];
+            for &i in order {
+                let b = bucket_of(centroids[i].axis(axis), ax.min, ax.size());
+                counts[b] += 1;
+                bounds[b] = Aabb::union(&bounds[b], &boxes[i]);
+            }
+
+            // sweep from the right to get suffix areas, then from the left
+            let mut right_area = [0.0; BUCKETS];
+            let mut right_count = [0usize; BUCKETS];
+            let mut acc = Aabb::empty();
+            let mut n = 0;
+            for b in (1..BUCKETS).rev() {
+                acc = Aabb::union(&acc, &bounds[b]);
+                n += counts[b];
+                right_area[b] = acc.surface_area();
+                right_count[b] = n;
+            }
+            let mut acc = Aabb::empty();
+            let mut n = 0;
+            for b in 0..BUCKETS - 1 {
+                acc = Aabb::union(&acc, &bounds[b]);
+                n += counts[b];
+                if n == 0 || right_count[b + 1] == 0 {
+                    continue;
+                }
+                let cost = TRAVERSAL_COST
+                    + (acc.surface_area() * n as f64
+                        + right_area[b + 1] * right_count[b + 1] as f64)
+                        / parent_area;
+                if cost < best_cost {
+                    best_cost = cost;
+                    best = Some((axis, b));
+                }
+            }
+        }
+
+        if best_cost < leaf_cost || order.len() > MAX_LEAF_SIZE {
+            best
+        } else {
+            None
+        }
+    }
+
+    /// Closest hit along the ray. `hit_primitive` is called with a primitive
+    /// index and the interval still worth searching.
+    pub(crate) fn hit<F>(
+        &self,
+        r: &Ray,
+        ray_t: &Interval,
+        mut hit_primitive: F,
+    ) -> Option<HitRecord>
+    where
+        F: FnMut(usize, &Interval) -> Option<HitRecord>,
+    {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let inv_direction = Vec3(
+            1.0 / r.direction.0,
+            1.0 / r.direction.1,
+            1.0 / r.direction.2,
+        );
+        let mut closest_so_far = ray_t.max;
+        let mut any_hit = None::<HitRecord>;
+        let mut stack = Vec::with_capacity(32);
+        stack.push(0);
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            let search = Interval::new(ray_t.min, closest_so_far);
+            if !node.bbox.hit_inverse(&r.origin, &inv_direction, &search) {
+                continue;
+            }
+            match node.kind {
+                NodeKind::Leaf { start, count } => {
+                    for &i in &self.order[start..start + count] {
+                        if let Some(hit) =
+                            hit_primitive(i, &Interval::new(ray_t.min, closest_so_far))
+                        {
+                            closest_so_far = hit.t;
+                            any_hit = Some(hit);
+                        }
+                    }
+                }
+                NodeKind::Interior { second, axis } => {
+                    // visit the child nearer the ray origin first
+                    if inv_direction.axis(axis) < 0.0 {
+                        stack.push(index + 1);
+                        stack.push(second);
+                    } else {
+                        stack.push(second);
+                        stack.push(index + 1);
+                    }
+                }
+            }
+        }
+        any_hit
+    }
+}
+
+fn bucket_of(x: f64, min: f64, extent: f64) -> usize {
+    let b = ((x - min) / extent * BUCKETS as f64) as usize;
+    b.min(BUCKETS - 1)
+}
+
+/// Moves every index satisfying `pred` to the front, returning how many did.
+fn partition(order: &mut [usize], pred: impl Fn(usize) -> bool) -> usize {
+    let mut mid = 0;
+    for i in 0..order.len() {
+        if pred(order[i]) {
+            order.swap(i, mid);
+            mid += 1;
+        }
+    }
+    mid
+}
+
+/// Bounding volume hierarchy over the objects of a `HittableList`.
+pub struct Bvh {
+    objects: Vec<Box<dyn Hit>>,
+    tree: BvhTree,
+}
+
+impl Bvh {
+    pub fn new(list: HittableList) -> Self {
+        let objects = list.objects;
+        let boxes: Vec<Aabb> = objects.iter().map(|o| o.bounding_box()).collect();
+        let tree = BvhTree::build(&boxes);
+        Self { objects, tree }
+    }
+
+    pub fn obj(list: HittableList) -> Box<dyn Hit> {
+        Box::new(Self::new(list))
+    }
+}
+
+impl From<HittableList> for Bvh {
+    fn from(list: HittableList) -> Self {
+        Self::new(list)
+    }
+}
+
+impl Hit for Bvh {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        self.tree
+            .hit(r, ray_t, |i, ray_t| self.objects[i].hit(r, ray_t))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.tree.bounding_box()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_bvh_matches_list() {
+    use crate::material::Lambertian;
+    use crate::math::random_f64;
+    use crate::sphere::Sphere;
+
+    let mut list = HittableList::new();
+    let mut shadow = HittableList::new();
+    let material = Lambertian::obj(Vec3(0.5, 0.5, 0.5));
+    for _ in 0..200 {
+        let center = Vec3::random_mm(-10.0, 10.0);
+        let radius = random_f64(0.1, 1.0);
+        list.add(Sphere::obj(center, radius, material.clone()));
+        shadow.add(Sphere::obj(center, radius, material.clone()));
+    }
+    let bvh = Bvh::new(list);
+    let ray_t = Interval::new(0.001, f64::MAX);
+    for _ in 0..500 {
+        let r = Ray {
+            origin: Vec3::random_mm(-15.0, 15.0),
+            direction: Vec3::unit_random(),
+            time: 0.0,
+            wavelength: None,
+        };
+        let expected = (&shadow).hit(&r, &ray_t).map(|h| h.t);
+        let actual = bvh.hit(&r, &ray_t).map(|h| h.t);
+        assert_eq!(expected, actual);
+    }
+}
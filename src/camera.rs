@@ -1,12 +1,18 @@
 use std::default::Default;
-use std::io::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
-use crate::color::write_color;
-use crate::hittable::Hit;
+use crate::film::Film;
+use crate::hittable::{Hit, HitRecord};
 use crate::interval::Interval;
+use crate::material::Scatter;
 use crate::math::degrees_to_radians;
 use crate::math::random;
+use crate::math::random_f64;
 use crate::ray::Ray;
+use crate::scene::Scene;
+use crate::spectrum::sample_wavelength;
 use crate::v3;
 use crate::vec3::{Color, Point, Vec3};
 use crate::vec3::{cross, unit_vector};
@@ -19,6 +25,13 @@ pub struct CameraParameters {
     pub focal_length: f64,
     pub focus_distance: f64,
     pub defocus_angle: f64,
+    /// the direction that appears up in the image
+    pub vup: Vec3,
+    /// Rays are sent at random times between the shutter opening and
+    /// closing, so anything moving in between is blurred. Moving spheres
+    /// and animated instances take their times on the same clock.
+    pub shutter_open: f64,
+    pub shutter_close: f64,
 }
 
 impl Default for CameraParameters {
@@ -30,6 +43,9 @@ impl Default for CameraParameters {
             defocus_angle: 0.0,
             focal_length: 1.0,
             focus_distance: 1.0,
+            vup: v3!(0, 1, 0),
+            shutter_open: 0.0,
+            shutter_close: 0.0,
         }
     }
 }
@@ -40,6 +56,11 @@ pub struct RenderParameters {
     pub aspect_ratio: f64,
     pub samples_per_pixel: f64,
     pub max_bounces: f64,
+    /// worker threads used by `Camera::render`
+    pub threads: usize,
+    /// Traces one wavelength per sample so dispersive materials split
+    /// light into colors. Costs more samples for the same noise.
+    pub spectral: bool,
 }
 
 impl Default for RenderParameters {
@@ -49,6 +70,8 @@ impl Default for RenderParameters {
             aspect_ratio: 16.0 / 9.0,
             max_bounces: 50.,
             samples_per_pixel: 100.,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            spectral: false,
         }
     }
 }
@@ -74,11 +97,8 @@ impl Camera {
         let viewport_height = 2.0 * h * c_params.focus_distance;
         let viewport_width = viewport_height * r_params.image_width / image_height;
 
-        // parametarize in future?
-        let vup = v3!(0, 1, 0);
-
         let w = unit_vector(&(c_params.look_from - c_params.look_at));
-        let u = unit_vector(&cross(&vup, &w));
+        let u = unit_vector(&cross(&c_params.vup, &w));
         let v = cross(&w, &u);
 
         let viewport_u = viewport_width * u;
@@ -114,29 +134,54 @@ impl Camera {
 }
 
 impl Camera {
-    pub fn render(&self, mut f: impl Write, world: impl Hit) {
-        write!(
-            f,
-            "P3\n{} {}\n255\n",
-            self.r_params.image_width, self.image_height
-        )
-        .expect("write failed");
-        for j in 0..self.image_height as i64 {
-            eprint!("\r       ");
-            eprint!("\r{}%", ((j as f64 / self.image_height) * 100.0).ceil());
-            for i in 0..self.r_params.image_width as i64 {
-                let mut color = Vec3::zero();
-                for _ in 0..self.r_params.samples_per_pixel as i64 {
-                    let r = self.get_ray(i as f64, j as f64);
-                    color += self.ray_color(&r, &world, self.r_params.max_bounces as u32);
-                }
-                write_color(&mut f, &(color * self.pixel_samples_scale)).expect("io error");
+    /// Renders the scene into a `Film` of linear radiance. Scanlines are
+    /// handed out to `r_params.threads` workers as they free up.
+    pub fn render(&self, scene: &Scene<impl Hit>) -> Film {
+        let width = self.r_params.image_width as usize;
+        let height = self.image_height as usize;
+        let mut film = Film::new(width, height);
+        let rows = Mutex::new(film.rows_mut().enumerate());
+        let rows_done = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for _ in 0..self.r_params.threads.max(1) {
+                scope.spawn(|| {
+                    loop {
+                        // hold the lock only long enough to claim a row
+                        let Some((j, row)) = rows.lock().expect("poisoned").next() else {
+                            break;
+                        };
+                        for (i, pixel) in row.iter_mut().enumerate() {
+                            *pixel = self.render_pixel(i, j, scene);
+                        }
+                        let done = rows_done.fetch_add(1, Ordering::Relaxed) + 1;
+                        eprint!("\r{:>3}%", (done * 100) / height);
+                    }
+                });
             }
-        }
+        });
         eprintln!();
+        film
+    }
+
+    fn render_pixel(&self, i: usize, j: usize, scene: &Scene<impl Hit>) -> Color {
+        let mut color = Vec3::zero();
+        let samples = self.r_params.samples_per_pixel as i64;
+        for s in 0..samples {
+            let mut r = self.get_ray(i as f64, j as f64);
+            let mut weight = Color::one();
+            if self.r_params.spectral {
+                // stratified, so each pixel sees the whole spectrum
+                let (wavelength, w) = sample_wavelength((s as f64 + random()) / samples as f64);
+                r.wavelength = Some(wavelength);
+                weight = w;
+            }
+            color += weight * self.ray_color(&r, scene, self.r_params.max_bounces as u32);
+        }
+        color * self.pixel_samples_scale
     }
 
-    pub fn ray_color<T>(&self, r: &Ray, world: &T, remaining_bounces: u32) -> Color
+    pub fn ray_color<T>(&self, r: &Ray, scene: &Scene<T>, remaining_bounces: u32) -> Color
     where
         T: Hit,
     {
@@ -145,21 +190,51 @@ impl Camera {
         }
 
         // ray hit something
-        if let Some(hit) = world.hit(r, &Interval::new(0.001, f64::MAX)) {
+        if let Some(hit) = scene.world.hit(r, &Interval::new(0.001, f64::MAX)) {
+            let emitted = hit.material.emitted(r, &hit);
             // something reflected ray
-            if let Some(scatter) = hit.material.scatter(r, &hit) {
-                scatter.color_attenuation
-                    * self.ray_color(&scatter.ray, world, remaining_bounces - 1)
+            if let Some(mut scatter) = hit.material.scatter(r, &hit) {
+                self.sample_background(r, &hit, &mut scatter, scene);
+                emitted
+                    + scatter.color_attenuation
+                        * self.ray_color(&scatter.ray, scene, remaining_bounces - 1)
             } else {
-                Color::zero()
+                emitted
             }
         } else {
-            let unit_direction = unit_vector(&r.direction);
-            let a = 0.5 * (unit_direction.1 + 1.0);
-            (1.0 - a) * Color::one() + (a * Vec3(0.5, 0.7, 1.0))
+            scene.background.color(r)
         }
     }
 
+    /// One-sample multiple importance sampling for diffuse bounces: half the
+    /// time the bounce is redirected toward a direction picked by the
+    /// background, and either way it is weighted by the combined density.
+    fn sample_background<T: Hit>(
+        &self,
+        r: &Ray,
+        hit: &HitRecord,
+        scatter: &mut Scatter,
+        scene: &Scene<T>,
+    ) {
+        let material = &hit.material;
+        if material.scattering_pdf(r, hit, &scatter.ray.direction) <= 0.0 {
+            return;
+        }
+        let Some(toward_background) = scene.background.sample() else {
+            return;
+        };
+        if random() < 0.5 {
+            scatter.ray.direction = toward_background;
+        }
+        let material_pdf = material.scattering_pdf(r, hit, &scatter.ray.direction);
+        let pdf = 0.5 * material_pdf + 0.5 * scene.background.pdf(&scatter.ray.direction);
+        scatter.color_attenuation = if pdf > 0.0 {
+            scatter.color_attenuation * (material_pdf / pdf)
+        } else {
+            Color::zero()
+        };
+    }
+
     fn get_ray(&self, i: f64, j: f64) -> Ray {
         let offset = Self::sample_square();
         let pixel_sample = self.pixel_00_loc
@@ -176,6 +251,8 @@ impl Camera {
         Ray {
             direction: ray_direction,
             origin: ray_origin,
+            time: random_f64(self.c_params.shutter_open, self.c_params.shutter_close),
+            wavelength: None,
         }
     }
 
@@ -1,28 +1,71 @@
+use crate::film::Film;
 use crate::vec3::Color;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 
-use crate::interval::Interval;
+/// Relative luminance of a linear Rec. 709 color.
+pub fn luminance(color: &Color) -> f64 {
+    0.2126 * color.0 + 0.7152 * color.1 + 0.0722 * color.2
+}
 
-static INTENSITY: Interval = Interval {
-    min: 0.0,
-    max: 0.999,
-};
+/// sRGB transfer function applied to a linear component.
+pub fn linear_to_srgb(component: f64) -> f64 {
+    if component <= 0.0031308 {
+        12.92 * component
+    } else {
+        1.055 * component.powf(1.0 / 2.4) - 0.055
+    }
+}
 
-fn linear_to_gamma(component: f64) -> f64 {
-    if component > 0.0 {
-        f64::sqrt(component)
+/// Inverse of `linear_to_srgb`, for decoding 8 and 16 bit images.
+pub fn srgb_to_linear(component: f64) -> f64 {
+    if component <= 0.04045 {
+        component / 12.92
     } else {
-        component
+        ((component + 0.055) / 1.055).powf(2.4)
     }
 }
 
+/// Maps `[0, 1]` onto the integers `0..=max`, clamping out of range values.
+pub fn quantize(component: f64, max: u32) -> u32 {
+    (component.clamp(0.0, 1.0) * max as f64 + 0.5) as u32
+}
+
+/// Writes one sRGB encoded 8 bit pixel, as the PNG encoder would store it.
 pub fn write_color(mut f: impl Write, color: &Color) -> Result<(), std::io::Error> {
-    let r = linear_to_gamma(color.0);
-    let g = linear_to_gamma(color.1);
-    let b = linear_to_gamma(color.2);
-    let r = (256.0 * INTENSITY.clamp(r)) as i32;
-    let g = (256.0 * INTENSITY.clamp(g)) as i32;
-    let b = (256.0 * INTENSITY.clamp(b)) as i32;
+    let r = quantize(linear_to_srgb(color.0), 255);
+    let g = quantize(linear_to_srgb(color.1), 255);
+    let b = quantize(linear_to_srgb(color.2), 255);
 
     writeln!(f, "{} {} {}", r, g, b)
 }
+
+/// Writes the film as an ASCII P3 portable pixmap.
+pub fn write_ppm(f: impl Write, film: &Film) -> Result<(), std::io::Error> {
+    let mut f = BufWriter::new(f);
+    write!(f, "P3\n{} {}\n255\n", film.width(), film.height())?;
+    for color in film.pixels() {
+        write_color(&mut f, color)?;
+    }
+    f.flush()
+}
+
+#[cfg(test)]
+#[test]
+fn test_write_color() {
+    use crate::vec3::Vec3;
+
+    // 8 bit sRGB: mid gray is 188, where plain gamma 2 gave 181
+    let mut out = Vec::new();
+    write_color(&mut out, &Vec3(0.0, 0.5, 1.0)).unwrap();
+    write_color(&mut out, &Vec3(-1.0, 2.0, 0.18)).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "0 188 255\n0 255 118\n");
+
+    let mut film = Film::new(2, 1);
+    film.set(1, 0, Vec3(0.5, 0.5, 0.5));
+    let mut out = Vec::new();
+    write_ppm(&mut out, &film).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "P3\n2 1\n255\n0 0 0\n188 188 188\n"
+    );
+}
@@ -0,0 +1,188 @@
+use crate::background::Background;
+use crate::color::luminance;
+use crate::film::Film;
+use crate::image;
+use crate::math::{degrees_to_radians, random};
+use crate::ray::Ray;
+use crate::vec3::{Color, Vec3, unit_vector};
+use std::f64::consts::PI;
+use std::path::Path;
+
+/// Piecewise constant distribution over bins `0..n`, proportional to the
+/// weights it was built from.
+struct Distribution1D {
+    cdf: Vec<f64>,
+}
+
+impl Distribution1D {
+    fn new(weights: &[f64]) -> Self {
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        cdf.push(0.0);
+        for w in weights {
+            cdf.push(cdf.last().unwrap() + w.max(0.0));
+        }
+        let total = *cdf.last().unwrap();
+        if total > 0.0 {
+            cdf.iter_mut().for_each(|c| *c /= total);
+        }
+        Self { cdf }
+    }
+
+    /// bin whose share of the cdf contains `u` in `[0, 1)`
+    fn sample(&self, u: f64) -> usize {
+        let bins = self.cdf.len() - 1;
+        self.cdf[1..].partition_point(|&c| c <= u).min(bins - 1)
+    }
+
+    /// probability of picking bin `i`
+    fn probability(&self, i: usize) -> f64 {
+        self.cdf[i + 1] - self.cdf[i]
+    }
+}
+
+/// Equirectangular environment image lighting the scene from infinitely far
+/// away. The image center looks down `-z`, the top row straight up `+y`.
+///
+/// Directions are importance sampled in proportion to pixel luminance, so
+/// small bright light sources such as a sun are found by diffuse bounces
+/// instead of being hit by chance.
+pub struct EnvironmentMap {
+    image: Film,
+    intensity: f64,
+    /// radians about `+y`
+    rotation: f64,
+    rows: Distribution1D,
+    columns: Vec<Distribution1D>,
+}
+
+impl EnvironmentMap {
+    pub fn new(image: Film) -> Self {
+        let (width, height) = (image.width(), image.height());
+        assert!(width > 0 && height > 0, "environment image is empty");
+        let mut row_weights = Vec::with_capacity(height);
+        let mut columns = Vec::with_capacity(height);
+        for (j, row) in image.rows().enumerate() {
+            // rows near the poles cover less of the sphere
+            let sin_theta = (PI * (j as f64 + 0.5) / height as f64).sin();
+            let weights: Vec<f64> = row.iter().map(|c| luminance(c) * sin_theta).collect();
+            row_weights.push(weights.iter().sum());
+            columns.push(Distribution1D::new(&weights));
+        }
+        Self {
+            image,
+            intensity: 1.0,
+            rotation: 0.0,
+            rows: Distribution1D::new(&row_weights),
+            columns,
+        }
+    }
+
+    /// Loads a Radiance `.hdr` or OpenEXR `.exr` image.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, std::io::Error> {
+        Ok(Self::new(image::load(path)?))
+    }
+
+    /// Scales the radiance of the whole map.
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Turns the map about the up axis.
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees_to_radians(degrees);
+        self
+    }
+
+    /// Pixel seen along `direction`, with the polar angle from `+y`.
+    fn pixel(&self, direction: &Vec3) -> (usize, usize, f64) {
+        let d = unit_vector(direction);
+        let theta = d.1.clamp(-1.0, 1.0).acos();
+        let phi = f64::atan2(d.0, -d.2) - self.rotation;
+        let u = (0.5 + phi / (2.0 * PI)).rem_euclid(1.0);
+        let v = theta / PI;
+        let (width, height) = (self.image.width(), self.image.height());
+        let i = ((u * width as f64) as usize).min(width - 1);
+        let j = ((v * height as f64) as usize).min(height - 1);
+        (i, j, theta)
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn color(&self, r: &Ray) -> Color {
+        let (i, j, _) = self.pixel(&r.direction);
+        self.intensity * self.image.get(i, j)
+    }
+
+    fn sample(&self) -> Option<Vec3> {
+        if *self.rows.cdf.last().unwrap() <= 0.0 {
+            return None;
+        }
+        let j = self.rows.sample(random());
+        let i = self.columns[j].sample(random());
+        let u = (i as f64 + random()) / self.image.width() as f64;
+        let v = (j as f64 + random()) / self.image.height() as f64;
+        let phi = 2.0 * PI * (u - 0.5) + self.rotation;
+        let theta = PI * v;
+        Some(Vec3(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            -theta.sin() * phi.cos(),
+        ))
+    }
+
+    fn pdf(&self, direction: &Vec3) -> f64 {
+        let (i, j, theta) = self.pixel(direction);
+        let sin_theta = theta.sin();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        let (width, height) = (self.image.width() as f64, self.image.height() as f64);
+        // density over the unit square, then over solid angle
+        let p = self.rows.probability(j) * self.columns[j].probability(i) * width * height;
+        p / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_environment_pdf_integrates_to_one() {
+    let mut image = Film::new(64, 32);
+    for j in 0..32 {
+        for i in 0..64 {
+            image.set(i, j, Color::one() * 0.1);
+        }
+    }
+    // a small sun
+    image.set(40, 10, Vec3(5000., 4000., 3000.));
+    let env = EnvironmentMap::new(image).with_rotation(30.0);
+
+    // sum pdf * solid angle over pixel centers
+    let mut total = 0.0;
+    for j in 0..32 {
+        let theta0 = PI * j as f64 / 32.0;
+        let theta1 = PI * (j + 1) as f64 / 32.0;
+        let theta = 0.5 * (theta0 + theta1);
+        let solid_angle = (2.0 * PI / 64.0) * (theta0.cos() - theta1.cos());
+        for i in 0..64 {
+            let phi = 2.0 * PI * ((i as f64 + 0.5) / 64.0 - 0.5) + env.rotation;
+            let d = Vec3(
+                theta.sin() * phi.sin(),
+                theta.cos(),
+                -theta.sin() * phi.cos(),
+            );
+            assert_eq!(env.pixel(&d).0, i);
+            total += env.pdf(&d) * solid_angle;
+        }
+    }
+    assert!((total - 1.0).abs() < 0.01);
+
+    // nearly every sample lands on the sun
+    let sun = (0..1000)
+        .filter(|_| {
+            let (i, j, _) = env.pixel(&env.sample().unwrap());
+            (i, j) == (40, 10)
+        })
+        .count();
+    assert!(sun > 900);
+}
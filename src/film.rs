@@ -0,0 +1,83 @@
+use crate::vec3::Color;
+
+/// Rendered image holding linear, unclamped radiance per pixel.
+///
+/// Pixels are stored row-major with row 0 at the top of the image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Film {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Film {
+    /// Black film of the given size.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Color::zero(); width * height],
+        }
+    }
+
+    /// Wraps existing row-major pixels. Panics if the length does not match.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(pixels.len(), width * height, "pixel count mismatch");
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        &mut self.pixels
+    }
+
+    /// pixel in column `i` of row `j`
+    pub fn get(&self, i: usize, j: usize) -> Color {
+        self.pixels[j * self.width + i]
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, color: Color) {
+        self.pixels[j * self.width + i] = color;
+    }
+
+    pub fn rows(&self) -> std::slice::Chunks<'_, Color> {
+        self.pixels.chunks(self.width.max(1))
+    }
+
+    pub fn rows_mut(&mut self) -> std::slice::ChunksMut<'_, Color> {
+        self.pixels.chunks_mut(self.width.max(1))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_film() {
+    use crate::vec3::Vec3;
+
+    let mut film = Film::new(3, 2);
+    assert_eq!(film.pixels(), [Color::zero(); 6]);
+    film.set(2, 0, Vec3(1., 2., 3.));
+    film.set(0, 1, Vec3(4., 5., 6.));
+    // row-major with the top row first
+    assert_eq!(film.pixels()[2], Vec3(1., 2., 3.));
+    assert_eq!(film.pixels()[3], Vec3(4., 5., 6.));
+    let rows: Vec<_> = film.rows().collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1][0], film.get(0, 1));
+    assert_eq!(Film::from_pixels(3, 2, film.pixels().to_vec()), film);
+}
@@ -0,0 +1,311 @@
+//! Minimal JSON reader, just enough for glTF documents.
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// members in document order
+    Object(Vec<(String, Json)>),
+}
+
+static NULL: Json = Json::Null;
+
+impl Json {
+    /// Member `key` of an object, or `Null` when missing or not an object.
+    pub(crate) fn get(&self, key: &str) -> &Json {
+        match self {
+            Self::Object(members) => members
+                .iter()
+                .find(|(k, _)| k == key)
+                .map_or(&NULL, |(_, v)| v),
+            _ => &NULL,
+        }
+    }
+
+    /// Element `i` of an array, or `Null`.
+    pub(crate) fn at(&self, i: usize) -> &Json {
+        match self {
+            Self::Array(items) => items.get(i).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+
+    pub(crate) fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Non-negative integral numbers, as used for indices and counts.
+    /// Numbers too large for `usize` are `None` rather than saturated.
+    pub(crate) fn as_usize(&self) -> Option<usize> {
+        self.as_f64()
+            .filter(|n| *n >= 0.0 && n.fract() == 0.0 && *n < usize::MAX as f64)
+            .map(|n| n as usize)
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Items of an array; empty for anything else.
+    pub(crate) fn items(&self) -> &[Json] {
+        match self {
+            Self::Array(items) => items,
+            _ => &[],
+        }
+    }
+
+    /// An array of exactly `N` numbers.
+    pub(crate) fn as_floats<const N: usize>(&self) -> Option<[f64; N]> {
+        let items = self.items();
+        if items.len() != N {
+            return None;
+        }
+        let mut out = [0.0; N];
+        for (o, item) in out.iter_mut().zip(items) {
+            *o = item.as_f64()?;
+        }
+        Some(out)
+    }
+}
+
+fn invalid(msg: &str, pos: usize) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("json: {msg} at byte {pos}"),
+    )
+}
+
+/// Nesting limit, so hostile input cannot overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+pub(crate) fn parse(data: &[u8]) -> Result<Json, std::io::Error> {
+    let mut parser = Parser { data, pos: 0 };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos != data.len() {
+        return Err(invalid("trailing characters", parser.pos));
+    }
+    Ok(value)
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), std::io::Error> {
+        if self.peek() != Some(byte) {
+            return Err(invalid(&format!("expected '{}'", byte as char), self.pos));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, std::io::Error> {
+        if !self.data[self.pos..].starts_with(word.as_bytes()) {
+            return Err(invalid("unexpected character", self.pos));
+        }
+        self.pos += word.len();
+        Ok(value)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, std::io::Error> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("nested too deeply", self.pos));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(depth),
+            Some(b'[') => self.array(depth),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(invalid("unexpected character", self.pos)),
+            None => Err(invalid("unexpected end of input", self.pos)),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Json, std::io::Error> {
+        self.expect(b'{')?;
+        let mut members = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            members.push((key, self.value(depth + 1)?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return Err(invalid("expected ',' or '}'", self.pos)),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Json, std::io::Error> {
+        self.expect(b'[')?;
+        let mut items = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(invalid("expected ',' or ']'", self.pos)),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, std::io::Error> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Json::Number)
+            .ok_or_else(|| invalid("bad number", start))
+    }
+
+    fn hex4(&mut self) -> Result<u32, std::io::Error> {
+        let digits = self
+            .data
+            .get(self.pos..self.pos + 4)
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .ok_or_else(|| invalid("bad unicode escape", self.pos))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+
+    fn string(&mut self) -> Result<String, std::io::Error> {
+        self.expect(b'"')?;
+        let mut out = Vec::new();
+        loop {
+            let Some(byte) = self.peek() else {
+                return Err(invalid("unterminated string", self.pos));
+            };
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let Some(escape) = self.peek() else {
+                        return Err(invalid("unterminated string", self.pos));
+                    };
+                    self.pos += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let mut code = self.hex4()?;
+                            // a high surrogate must pair with a low one
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.data[self.pos..].starts_with(b"\\u")
+                            {
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(invalid("bad surrogate pair", self.pos));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            char::from_u32(code)
+                                .ok_or_else(|| invalid("bad unicode escape", self.pos))?
+                        }
+                        _ => return Err(invalid("bad escape", self.pos - 1)),
+                    };
+                    let mut buf = [0; 4];
+                    out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+                _ => out.push(byte),
+            }
+        }
+        String::from_utf8(out).map_err(|_| invalid("string is not valid UTF-8", self.pos))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_json() {
+    let doc = parse(
+        r#" { "a": [1, -2.5e1, true, null], "b": {"c": "x\"é😀\n", "e": "\u00e9\ud83d\ude00"}, "d": [] } "#
+        .as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(doc.get("a").at(1).as_f64(), Some(-25.0));
+    assert_eq!(doc.get("a").at(0).as_usize(), Some(1));
+    assert_eq!(doc.get("a").at(1).as_usize(), None);
+    assert_eq!(Json::Number(1e300).as_usize(), None);
+    assert_eq!(doc.get("a").at(2), &Json::Bool(true));
+    assert!(doc.get("a").at(3).is_null());
+    assert!(doc.get("a").at(9).is_null());
+    assert_eq!(doc.get("b").get("c").as_str(), Some("x\"é😀\n"));
+    assert_eq!(doc.get("b").get("e").as_str(), Some("é😀"));
+    assert!(doc.get("d").items().is_empty());
+    assert!(doc.get("missing").get("deeper").is_null());
+
+    for bad in [
+        &b"{\"a\" 1}"[..],
+        b"[1, 2",
+        b"\"abc",
+        b"[1] x",
+        b"{\"a\": tru}",
+    ] {
+        assert!(parse(bad).is_err());
+    }
+    let err = parse(b"[1, 2,]").err().unwrap();
+    assert_eq!(err.to_string(), "json: unexpected character at byte 6");
+}
@@ -0,0 +1,807 @@
+//! Import of glTF 2.0 scenes, from `.gltf` files with their buffers and
+//! images or from self-contained `.glb` files.
+
+mod json;
+
+use crate::camera::CameraParameters;
+use crate::hittable::HittableList;
+use crate::image::{read_jpeg, read_png};
+use crate::material::{Dielectric, DiffuseLight, Lambertian, Material, Metal};
+use crate::math::Transform;
+use crate::mesh::TriangleMesh;
+use crate::texture::{AddressMode, Filter, ImageTexture, Texture};
+use crate::vec3::{Color, Point, Vec3, unit_vector};
+use json::Json;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("gltf: {msg}"))
+}
+
+/// Extensions that only refine materials we already understand.
+const SUPPORTED_EXTENSIONS: [&str; 4] = [
+    "KHR_materials_emissive_strength",
+    "KHR_materials_ior",
+    "KHR_materials_transmission",
+    "KHR_materials_volume",
+];
+
+/// The default scene of a glTF file.
+pub struct GltfScene {
+    /// one mesh per node that has one, in world space
+    pub world: HittableList,
+    /// the first perspective camera met walking the node hierarchy
+    pub camera: Option<CameraParameters>,
+    /// aspect ratio the camera was authored for, when it specifies one
+    pub aspect_ratio: Option<f64>,
+}
+
+/// Loads a `.gltf` or `.glb` file.
+///
+/// Materials map onto the closest crate material: emissive materials become
+/// `DiffuseLight`, `KHR_materials_transmission` ones `Dielectric` tinted by
+/// any `KHR_materials_volume` attenuation, mostly metallic ones `Metal` and
+/// the rest `Lambertian`, with the base color texture and factor as albedo.
+/// Metallic-roughness, normal and occlusion textures are ignored. Primitives
+/// without a material are light gray.
+pub fn load_gltf(path: impl AsRef<Path>) -> Result<GltfScene, std::io::Error> {
+    let path = path.as_ref();
+    let data = std::fs::read(path)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    if data.starts_with(b"glTF") {
+        read_glb(&data, dir)
+    } else {
+        read_document(&data, None, dir)
+    }
+}
+
+/// Binary container: a 12 byte header, then a JSON chunk and an optional
+/// binary chunk, all little endian.
+fn read_glb(data: &[u8], dir: &Path) -> Result<GltfScene, std::io::Error> {
+    let word = |at: usize| {
+        data.get(at..at + 4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize)
+            .ok_or_else(|| invalid("truncated glb"))
+    };
+    if word(4)? != 2 {
+        return Err(invalid("unsupported glb version"));
+    }
+    let length = word(8)?.min(data.len());
+    let mut chunks = vec![];
+    let mut pos = 12;
+    while pos + 8 <= length {
+        let (size, kind) = (word(pos)?, word(pos + 4)?);
+        let chunk = data
+            .get(pos + 8..pos + 8 + size)
+            .ok_or_else(|| invalid("truncated glb chunk"))?;
+        chunks.push((kind, chunk));
+        pos += 8 + size;
+    }
+    const JSON: usize = 0x4E4F_534A;
+    const BIN: usize = 0x004E_4942;
+    match chunks.as_slice() {
+        [(JSON, json), rest @ ..] => {
+            let bin = rest.iter().find(|(kind, _)| *kind == BIN).map(|(_, c)| *c);
+            read_document(json, bin, dir)
+        }
+        _ => Err(invalid("glb does not start with a JSON chunk")),
+    }
+}
+
+fn read_document(data: &[u8], bin: Option<&[u8]>, dir: &Path) -> Result<GltfScene, std::io::Error> {
+    let json = json::parse(data).map_err(|e| invalid(&e.to_string()))?;
+    let version = json.get("asset").get("version").as_str().unwrap_or("");
+    if !version.starts_with("2.") {
+        return Err(invalid(&format!("unsupported version '{version}'")));
+    }
+    for required in json.get("extensionsRequired").items() {
+        let name = required.as_str().unwrap_or("");
+        if !SUPPORTED_EXTENSIONS.contains(&name) {
+            return Err(invalid(&format!(
+                "required extension {name} is not supported"
+            )));
+        }
+    }
+
+    let mut buffers = vec![];
+    for (i, buffer) in json.get("buffers").items().iter().enumerate() {
+        let data = match buffer.get("uri").as_str() {
+            Some(uri) => read_uri(uri, dir)?,
+            None if i == 0 => bin
+                .ok_or_else(|| invalid("buffer 0 has no uri and there is no glb chunk"))?
+                .to_vec(),
+            None => return Err(invalid(&format!("buffer {i} has no uri"))),
+        };
+        let length = buffer.get("byteLength").as_usize().unwrap_or(0);
+        if data.len() < length {
+            return Err(invalid(&format!(
+                "buffer {i} is shorter than its byteLength"
+            )));
+        }
+        buffers.push(data);
+    }
+
+    let mut doc = Document {
+        json: &json,
+        buffers,
+        dir,
+        textures: HashMap::new(),
+    };
+    let mut palette = vec![];
+    for material in json.get("materials").items() {
+        palette.push(doc.material(material)?);
+    }
+    palette.push(Lambertian::obj(Vec3(0.8, 0.8, 0.8)));
+
+    let scene_index = json.get("scene").as_usize().unwrap_or(0);
+    let scene = json.get("scenes").at(scene_index);
+    if scene.is_null() {
+        return Err(invalid("no scene"));
+    }
+
+    let mut out = GltfScene {
+        world: HittableList::new(),
+        camera: None,
+        aspect_ratio: None,
+    };
+    let nodes = json.get("nodes").items();
+    let mut visited = vec![false; nodes.len()];
+    let mut stack: Vec<(usize, Transform)> = scene
+        .get("nodes")
+        .items()
+        .iter()
+        .rev()
+        .map(|n| Ok((index(n, "scene node")?, Transform::identity())))
+        .collect::<Result<_, std::io::Error>>()?;
+    while let Some((n, parent)) = stack.pop() {
+        let node = nodes
+            .get(n)
+            .ok_or_else(|| invalid(&format!("node {n} does not exist")))?;
+        if std::mem::replace(&mut visited[n], true) {
+            return Err(invalid(&format!("node {n} appears twice in the hierarchy")));
+        }
+        let Some(local) = node_transform(node)? else {
+            continue;
+        };
+        let world = parent * local;
+
+        if let Some(mesh) = node.get("mesh").as_usize() {
+            let mesh = doc.mesh(mesh, &world, &palette)?;
+            if !mesh.is_empty() {
+                out.world.add(Box::new(mesh));
+            }
+        }
+        if out.camera.is_none()
+            && let Some(camera) = node.get("camera").as_usize()
+        {
+            let perspective = json.get("cameras").at(camera).get("perspective");
+            if let Some(yfov) = perspective.get("yfov").as_f64() {
+                let look_from = world.point(&Vec3::zero());
+                let forward = unit_vector(&world.vector(&Vec3(0., 0., -1.)));
+                out.camera = Some(CameraParameters {
+                    look_from,
+                    look_at: look_from + forward,
+                    vup: unit_vector(&world.vector(&Vec3(0., 1., 0.))),
+                    vfov: yfov.to_degrees(),
+                    ..Default::default()
+                });
+                out.aspect_ratio = perspective.get("aspectRatio").as_f64();
+            }
+        }
+        for child in node.get("children").items().iter().rev() {
+            stack.push((index(child, "child node")?, world));
+        }
+    }
+    Ok(out)
+}
+
+fn index(value: &Json, what: &str) -> Result<usize, std::io::Error> {
+    value
+        .as_usize()
+        .ok_or_else(|| invalid(&format!("bad {what} index")))
+}
+
+/// Reads a `data:` URI or a file relative to the document.
+fn read_uri(uri: &str, dir: &Path) -> Result<Vec<u8>, std::io::Error> {
+    if let Some(rest) = uri.strip_prefix("data:") {
+        let Some((header, payload)) = rest.split_once(',') else {
+            return Err(invalid("malformed data uri"));
+        };
+        if !header.ends_with(";base64") {
+            return Err(invalid("only base64 data uris are supported"));
+        }
+        return base64_decode(payload).ok_or_else(|| invalid("bad base64 in data uri"));
+    }
+    let path = dir.join(percent_decode(uri));
+    std::fs::read(&path).map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!("gltf: cannot read {}: {e}", path.display()),
+        )
+    })
+}
+
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    };
+    let text = text.trim_end_matches('=').as_bytes();
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for &c in text {
+        acc = (acc << 6) | value(c)? as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// A node's local transform, from `matrix` or from translation, rotation
+/// and scale applied in TRS order. `None` for a singular transform, which
+/// flattens the node and everything below it to nothing.
+fn node_transform(node: &Json) -> Result<Option<Transform>, std::io::Error> {
+    if !node.get("matrix").is_null() {
+        let m = node
+            .get("matrix")
+            .as_floats::<16>()
+            .ok_or_else(|| invalid("node matrix needs 16 numbers"))?;
+        // stored column-major
+        let mut out = [[0.0; 4]; 4];
+        for (c, column) in m.chunks(4).enumerate() {
+            for (r, x) in column.iter().enumerate() {
+                out[r][c] = *x;
+            }
+        }
+        out[3] = [0., 0., 0., 1.];
+        return Ok(Transform::from_matrix(out));
+    }
+    let [tx, ty, tz] = node.get("translation").as_floats().unwrap_or([0.0; 3]);
+    let [x, y, z, w] = node.get("rotation").as_floats().unwrap_or([0., 0., 0., 1.]);
+    let [sx, sy, sz] = node.get("scale").as_floats().unwrap_or([1.0; 3]);
+    if sx * sy * sz == 0.0 {
+        return Ok(None);
+    }
+    let norm = (x * x + y * y + z * z + w * w).sqrt();
+    if norm == 0.0 {
+        return Err(invalid("node rotation is not a unit quaternion"));
+    }
+    Ok(Some(
+        Transform::translate(Vec3(tx, ty, tz))
+            * Transform::rotate_quaternion(x / norm, y / norm, z / norm, w / norm)
+            * Transform::scale(Vec3(sx, sy, sz)),
+    ))
+}
+
+/// Multiplies a base color texture by the material's color factor.
+struct Tinted {
+    texture: Arc<dyn Texture>,
+    tint: Color,
+}
+
+impl Texture for Tinted {
+    fn value(&self, u: f64, v: f64, p: &Point) -> Color {
+        self.texture.value(u, v, p) * self.tint
+    }
+}
+
+struct Document<'a> {
+    json: &'a Json,
+    buffers: Vec<Vec<u8>>,
+    dir: &'a Path,
+    /// decoded textures by texture index, shared between materials
+    textures: HashMap<usize, Arc<dyn Texture>>,
+}
+
+impl Document<'_> {
+    fn buffer_view(&self, index: usize) -> Result<(&[u8], Option<usize>), std::io::Error> {
+        let view = self.json.get("bufferViews").at(index);
+        let buffer = view
+            .get("buffer")
+            .as_usize()
+            .and_then(|b| self.buffers.get(b));
+        let Some(buffer) = buffer else {
+            return Err(invalid(&format!("buffer view {index} has no buffer")));
+        };
+        let field = |name: &str| match view.get(name) {
+            Json::Null => Ok(0),
+            value => value
+                .as_usize()
+                .ok_or_else(|| invalid(&format!("buffer view {index}: bad {name}"))),
+        };
+        let (offset, length) = (field("byteOffset")?, field("byteLength")?);
+        let data = offset
+            .checked_add(length)
+            .and_then(|end| buffer.get(offset..end))
+            .ok_or_else(|| invalid(&format!("buffer view {index} is out of range")))?;
+        Ok((data, view.get("byteStride").as_usize()))
+    }
+
+    /// An accessor's elements as a flat list of `components` numbers each,
+    /// with normalized integers mapped to `[0, 1]` or `[-1, 1]`.
+    fn accessor(&self, index: usize) -> Result<(Vec<f64>, usize), std::io::Error> {
+        let accessor = self.json.get("accessors").at(index);
+        let err = |msg: &str| invalid(&format!("accessor {index}: {msg}"));
+        if accessor.is_null() {
+            return Err(err("does not exist"));
+        }
+        if !accessor.get("sparse").is_null() {
+            return Err(err("sparse accessors are not supported"));
+        }
+        let count = accessor
+            .get("count")
+            .as_usize()
+            .ok_or_else(|| err("bad count"))?;
+        let components = match accessor.get("type").as_str() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            Some("MAT4") => 16,
+            _ => return Err(err("unsupported type")),
+        };
+        let normalized = accessor.get("normalized") == &Json::Bool(true);
+        let (size, scale) = match accessor.get("componentType").as_usize() {
+            Some(5120) => (1, i8::MAX as f64),
+            Some(5121) => (1, u8::MAX as f64),
+            Some(5122) => (2, i16::MAX as f64),
+            Some(5123) => (2, u16::MAX as f64),
+            Some(5125) => (4, 1.0),
+            Some(5126) => (4, 1.0),
+            _ => return Err(err("unsupported component type")),
+        };
+        let kind = accessor.get("componentType").as_usize().unwrap();
+
+        let len = count
+            .checked_mul(components)
+            .ok_or_else(|| err("bad count"))?;
+        let Some(view) = accessor.get("bufferView").as_usize() else {
+            // no view means all zeros
+            return Ok((vec![0.0; len], components));
+        };
+        let (data, stride) = self.buffer_view(view)?;
+        let element = size * components;
+        let stride = stride.unwrap_or(element);
+        if stride < element {
+            return Err(err("byteStride is shorter than an element"));
+        }
+        let offset = match accessor.get("byteOffset") {
+            Json::Null => 0,
+            value => value.as_usize().ok_or_else(|| err("bad byteOffset"))?,
+        };
+        // the last element must end within the view, which also bounds count
+        let end = match count {
+            0 => Some(0),
+            _ => stride
+                .checked_mul(count - 1)
+                .and_then(|n| n.checked_add(offset))
+                .and_then(|n| n.checked_add(element)),
+        };
+        if end.is_none_or(|end| end > data.len()) {
+            return Err(err("reads past the end of its buffer view"));
+        }
+
+        let mut out = Vec::with_capacity(len);
+        for i in 0..count {
+            let start = offset + i * stride;
+            for c in 0..components {
+                let b = &data[start + c * size..start + (c + 1) * size];
+                let x = match kind {
+                    5120 => b[0] as i8 as f64,
+                    5121 => b[0] as f64,
+                    5122 => i16::from_le_bytes([b[0], b[1]]) as f64,
+                    5123 => u16::from_le_bytes([b[0], b[1]]) as f64,
+                    5125 => u32::from_le_bytes(b.try_into().unwrap()) as f64,
+                    _ => f32::from_le_bytes(b.try_into().unwrap()) as f64,
+                };
+                out.push(if normalized { (x / scale).max(-1.0) } else { x });
+            }
+        }
+        Ok((out, components))
+    }
+
+    fn texture(&mut self, index: usize) -> Result<Arc<dyn Texture>, std::io::Error> {
+        if let Some(texture) = self.textures.get(&index) {
+            return Ok(Arc::clone(texture));
+        }
+        let json = self.json;
+        let texture = json.get("textures").at(index);
+        let source = texture
+            .get("source")
+            .as_usize()
+            .ok_or_else(|| invalid(&format!("texture {index} has no image source")))?;
+        let image = json.get("images").at(source);
+        let bytes = match (
+            image.get("uri").as_str(),
+            image.get("bufferView").as_usize(),
+        ) {
+            (Some(uri), _) => read_uri(uri, self.dir)?,
+            (None, Some(view)) => self.buffer_view(view)?.0.to_vec(),
+            _ => return Err(invalid(&format!("image {source} has no data"))),
+        };
+        let film = if bytes.starts_with(b"\x89PNG") {
+            read_png(bytes.as_slice())?
+        } else if bytes.starts_with(&[0xFF, 0xD8]) {
+            read_jpeg(bytes.as_slice())?
+        } else {
+            return Err(invalid(&format!("image {source} is neither PNG nor JPEG")));
+        };
+
+        let sampler = json
+            .get("samplers")
+            .at(texture.get("sampler").as_usize().unwrap_or(usize::MAX));
+        let address = match sampler.get("wrapS").as_usize() {
+            Some(33071) => AddressMode::Clamp,
+            Some(33648) => AddressMode::Mirror,
+            _ => AddressMode::Wrap,
+        };
+        let filter = match sampler.get("magFilter").as_usize() {
+            Some(9728) => Filter::Nearest,
+            _ => Filter::Bilinear,
+        };
+        let texture: Arc<dyn Texture> = Arc::new(
+            ImageTexture::new(film)
+                .with_address_mode(address)
+                .with_filter(filter),
+        );
+        self.textures.insert(index, Arc::clone(&texture));
+        Ok(texture)
+    }
+
+    /// A color factor, multiplied by a texture when one is referenced.
+    fn textured(
+        &mut self,
+        factor: Color,
+        texture: &Json,
+    ) -> Result<Arc<dyn Texture>, std::io::Error> {
+        match texture.get("index").as_usize() {
+            Some(index) => {
+                let texture = self.texture(index)?;
+                if factor == Color::one() {
+                    Ok(texture)
+                } else {
+                    Ok(Arc::new(Tinted {
+                        texture,
+                        tint: factor,
+                    }))
+                }
+            }
+            None => Ok(factor.into()),
+        }
+    }
+
+    fn material(&mut self, material: &Json) -> Result<Arc<dyn Material>, std::io::Error> {
+        let pbr = material.get("pbrMetallicRoughness");
+        let extensions = material.get("extensions");
+        let [r, g, b, _] = pbr.get("baseColorFactor").as_floats().unwrap_or([1.0; 4]);
+        let metallic = pbr.get("metallicFactor").as_f64().unwrap_or(1.0);
+        let roughness = pbr.get("roughnessFactor").as_f64().unwrap_or(1.0);
+        let [er, eg, eb] = material
+            .get("emissiveFactor")
+            .as_floats()
+            .unwrap_or([0.0; 3]);
+        let strength = extensions
+            .get("KHR_materials_emissive_strength")
+            .get("emissiveStrength")
+            .as_f64()
+            .unwrap_or(1.0);
+        let transmission = extensions
+            .get("KHR_materials_transmission")
+            .get("transmissionFactor")
+            .as_f64()
+            .unwrap_or(0.0);
+        let ior = extensions
+            .get("KHR_materials_ior")
+            .get("ior")
+            .as_f64()
+            .unwrap_or(1.5);
+
+        let emission = strength * Vec3(er, eg, eb);
+        Ok(if emission.0.max(emission.1).max(emission.2) > 0.0 {
+            DiffuseLight::obj(self.textured(emission, material.get("emissiveTexture"))?)
+        } else if transmission > 0.0 {
+            let volume = extensions.get("KHR_materials_volume");
+            let [ar, ag, ab] = volume
+                .get("attenuationColor")
+                .as_floats()
+                .unwrap_or([1.0; 3]);
+            let distance = volume
+                .get("attenuationDistance")
+                .as_f64()
+                .unwrap_or(f64::INFINITY);
+            Arc::new(Dielectric::new(ior).with_color_at_distance(Vec3(ar, ag, ab), distance))
+        } else {
+            let albedo = self.textured(Vec3(r, g, b), pbr.get("baseColorTexture"))?;
+            if metallic >= 0.5 {
+                Metal::obj(albedo, roughness)
+            } else {
+                Lambertian::obj(albedo)
+            }
+        })
+    }
+
+    /// All triangle primitives of a mesh as one mesh in world space.
+    fn mesh(
+        &self,
+        index: usize,
+        world: &Transform,
+        palette: &[Arc<dyn Material>],
+    ) -> Result<TriangleMesh, std::io::Error> {
+        let mesh = self.json.get("meshes").at(index);
+        if mesh.is_null() {
+            return Err(invalid(&format!("mesh {index} does not exist")));
+        }
+        // a mirroring transform turns the winding inside out
+        let flip = world.swaps_handedness();
+        let default_material = (palette.len() - 1) as u32;
+
+        let mut positions = vec![];
+        let mut normals = vec![];
+        let mut uvs = vec![];
+        let mut has_normals = false;
+        let mut has_uvs = false;
+        let mut faces = vec![];
+        let mut ids = vec![];
+        for primitive in mesh.get("primitives").items() {
+            let mode = primitive.get("mode").as_usize().unwrap_or(4);
+            // points and lines have no surface
+            if !(4..=6).contains(&mode) {
+                continue;
+            }
+            let attributes = primitive.get("attributes");
+            let position = attributes
+                .get("POSITION")
+                .as_usize()
+                .ok_or_else(|| invalid(&format!("mesh {index} primitive without POSITION")))?;
+            let (p, n) = self.accessor(position)?;
+            if n != 3 {
+                return Err(invalid(&format!("mesh {index}: POSITION is not VEC3")));
+            }
+            let base = positions.len() as u32;
+            let count = p.len() / 3;
+            positions.extend(p.chunks(3).map(|p| world.point(&Vec3(p[0], p[1], p[2]))));
+
+            match attributes.get("NORMAL").as_usize() {
+                Some(a) => {
+                    let (v, n) = self.accessor(a)?;
+                    if n != 3 {
+                        return Err(invalid(&format!("mesh {index}: NORMAL is not VEC3")));
+                    }
+                    has_normals = true;
+                    normals.extend(v.chunks(3).map(|n| world.normal(&Vec3(n[0], n[1], n[2]))));
+                }
+                None => normals.extend(std::iter::repeat_n(Vec3::zero(), count)),
+            }
+            match attributes.get("TEXCOORD_0").as_usize() {
+                Some(a) => {
+                    let (v, n) = self.accessor(a)?;
+                    if n != 2 {
+                        return Err(invalid(&format!("mesh {index}: TEXCOORD_0 is not VEC2")));
+                    }
+                    has_uvs = true;
+                    // glTF puts v = 0 at the top of the image
+                    uvs.extend(v.chunks(2).map(|t| (t[0], 1.0 - t[1])));
+                }
+                None => uvs.extend(std::iter::repeat_n((0.0, 0.0), count)),
+            }
+            if normals.len() != positions.len() || uvs.len() != positions.len() {
+                return Err(invalid(&format!("mesh {index}: attribute counts differ")));
+            }
+
+            let indices: Vec<u32> = match primitive.get("indices").as_usize() {
+                Some(a) => self.accessor(a)?.0.iter().map(|&i| i as u32).collect(),
+                None => (0..count as u32).collect(),
+            };
+            if let Some(&bad) = indices.iter().find(|&&i| i as usize >= count) {
+                return Err(invalid(&format!("mesh {index}: index {bad} out of range")));
+            }
+            let triangles: Vec<[u32; 3]> = match mode {
+                4 => indices
+                    .chunks_exact(3)
+                    .map(|t| [t[0], t[1], t[2]])
+                    .collect(),
+                // strips alternate their winding
+                5 => (2..indices.len())
+                    .map(|k| {
+                        let (a, b, c) = (indices[k - 2], indices[k - 1], indices[k]);
+                        if k % 2 == 0 { [a, b, c] } else { [b, a, c] }
+                    })
+                    .collect(),
+                _ => (2..indices.len())
+                    .map(|k| [indices[0], indices[k - 1], indices[k]])
+                    .collect(),
+            };
+            let material = match primitive.get("material").as_usize() {
+                Some(m) if m < palette.len() - 1 => m as u32,
+                Some(m) => return Err(invalid(&format!("material {m} does not exist"))),
+                None => default_material,
+            };
+            for [a, b, c] in triangles {
+                faces.push(if flip {
+                    [base + a, base + c, base + b]
+                } else {
+                    [base + a, base + b, base + c]
+                });
+                ids.push(material);
+            }
+        }
+
+        let mut mesh = TriangleMesh::new(positions, faces, Arc::clone(&palette[0]))
+            .with_face_materials(palette.to_vec(), ids);
+        if has_normals {
+            mesh = mesh.with_normals(normals);
+        }
+        if has_uvs {
+            mesh = mesh.with_uvs(uvs);
+        }
+        Ok(mesh)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_glb() {
+    use crate::hittable::Hit;
+    use crate::interval::Interval;
+    use crate::ray::Ray;
+
+    // one triangle in the z = 0 plane, moved 5 units down -z by its parent
+    // node, seen by a camera turned a quarter around y
+    let mut bin = vec![];
+    for x in [0f32, 0., 0., 1., 0., 0., 0., 1., 0.] {
+        bin.extend(x.to_le_bytes());
+    }
+    for i in [0u16, 1, 2, 0] {
+        bin.extend(i.to_le_bytes());
+    }
+    let json = r#"{
+        "asset": {"version": "2.0"},
+        "scene": 0,
+        "scenes": [{"nodes": [0, 2]}],
+        "nodes": [
+            {"translation": [0, 0, -5], "children": [1]},
+            {"mesh": 0, "scale": [2, 2, 2]},
+            {"camera": 0, "rotation": [0, 0.7071067811865476, 0, 0.7071067811865476]}
+        ],
+        "cameras": [{"type": "perspective", "perspective": {"yfov": 0.5, "aspectRatio": 1.5, "znear": 0.1}}],
+        "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1, "material": 0}]}],
+        "materials": [{"pbrMetallicRoughness": {"baseColorFactor": [1, 0, 0, 1], "metallicFactor": 0}}],
+        "buffers": [{"byteLength": 44}],
+        "bufferViews": [
+            {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+            {"buffer": 0, "byteOffset": 36, "byteLength": 6}
+        ],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3"},
+            {"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"}
+        ]
+    }"#;
+    let pack = |json: &str| {
+        let mut json = json.as_bytes().to_vec();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let mut glb = b"glTF".to_vec();
+        glb.extend(2u32.to_le_bytes());
+        glb.extend((12 + 8 + json.len() as u32 + 8 + bin.len() as u32).to_le_bytes());
+        glb.extend((json.len() as u32).to_le_bytes());
+        glb.extend(b"JSON");
+        glb.extend(&json);
+        glb.extend((bin.len() as u32).to_le_bytes());
+        glb.extend(b"BIN\0");
+        glb.extend(&bin);
+        glb
+    };
+    let glb = pack(json);
+
+    let scene = read_glb(&glb, Path::new(".")).unwrap();
+    assert_eq!(scene.world.objects.len(), 1);
+    let r = Ray {
+        origin: Vec3(0.5, 0.5, 0.),
+        direction: Vec3(0., 0., -1.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let hit = (&scene.world)
+        .hit(&r, &Interval::new(0.001, f64::MAX))
+        .expect("the scaled triangle covers (0.5, 0.5)");
+    assert!((hit.t - 5.0).abs() < 1e-12);
+    assert!(hit.front_face);
+
+    let camera = scene.camera.expect("camera node");
+    assert_eq!(scene.aspect_ratio, Some(1.5));
+    assert!((camera.vfov - 0.5f64.to_degrees()).abs() < 1e-12);
+    // -z turned a quarter around +y looks down -x
+    let forward = camera.look_at - camera.look_from;
+    assert!((forward - Vec3(-1., 0., 0.)).len() < 1e-12);
+
+    let mut truncated = glb.clone();
+    truncated.truncate(glb.len() - 8);
+    let length = truncated.len() as u32;
+    truncated[8..12].copy_from_slice(&length.to_le_bytes());
+    assert!(read_glb(&truncated, Path::new(".")).is_err());
+
+    // malformed accessors are errors, not panics
+    let error = |json: String| {
+        read_glb(&pack(&json), Path::new("."))
+            .err()
+            .unwrap()
+            .to_string()
+    };
+    let vec2_normals = json
+        .replace(r#""POSITION": 0}"#, r#""POSITION": 0, "NORMAL": 2}"#)
+        .replace(
+            r#""type": "SCALAR"}"#,
+            r#""type": "SCALAR"},
+            {"bufferView": 0, "componentType": 5126, "count": 2, "type": "VEC2"}"#,
+        );
+    assert_eq!(error(vec2_normals), "gltf: mesh 0: NORMAL is not VEC3");
+    let huge_count = json.replace(
+        r#""count": 3, "type": "VEC3""#,
+        r#""count": 9223372036854775808, "type": "VEC3""#,
+    );
+    assert_eq!(error(huge_count), "gltf: accessor 0: bad count");
+    let past_end = json.replace(
+        r#""count": 3, "type": "VEC3""#,
+        r#""count": 4, "type": "VEC3""#,
+    );
+    assert_eq!(
+        error(past_end),
+        "gltf: accessor 0: reads past the end of its buffer view"
+    );
+    let view = r#"{"buffer": 0, "byteOffset": 0, "byteLength": 36}"#;
+    let huge_length = json.replace(
+        view,
+        r#"{"buffer": 0, "byteOffset": 0, "byteLength": 1e300}"#,
+    );
+    assert_eq!(error(huge_length), "gltf: buffer view 0: bad byteLength");
+    let wrapping = json.replace(
+        view,
+        r#"{"buffer": 0, "byteOffset": 9223372036854775808, "byteLength": 9223372036854775808}"#,
+    );
+    assert_eq!(error(wrapping), "gltf: buffer view 0 is out of range");
+}
+
+#[cfg(test)]
+#[test]
+fn test_uri_decoding() {
+    assert_eq!(base64_decode("aGVsbG8gd29ybGQ=").unwrap(), b"hello world");
+    assert_eq!(base64_decode("AAEC/w==").unwrap(), [0, 1, 2, 255]);
+    assert!(base64_decode("a$b=").is_none());
+    assert_eq!(percent_decode("my%20model%2Fbin.bin"), "my model/bin.bin");
+    assert_eq!(
+        read_uri("data:application/octet-stream;base64,AAEC", Path::new(".")).unwrap(),
+        [0, 1, 2]
+    );
+}
@@ -1,20 +1,27 @@
+use crate::aabb::Aabb;
 use crate::interval::Interval;
 use crate::material::Material;
 use crate::ray::Ray;
 use crate::vec3::{Point, Vec3, dot};
-use std::rc::Rc;
+use std::sync::Arc;
 
-pub trait Hit {
+pub trait Hit: Send + Sync {
     fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord>;
+    /// Box enclosing everything this object can be hit at. Objects with no
+    /// extent return `Aabb::empty()`.
+    fn bounding_box(&self) -> Aabb;
 }
 
 #[derive(Clone)]
 pub struct HitRecord {
     pub p: Point,
     pub t: f64,
+    /// surface coordinates, for texture lookups
+    pub u: f64,
+    pub v: f64,
     pub normal: Vec3,
     pub front_face: bool,
-    pub material: Rc<dyn Material>,
+    pub material: Arc<dyn Material>,
 }
 
 impl HitRecord {
@@ -23,7 +30,7 @@ impl HitRecord {
         r: &Ray,
         u_out_norm: Vec3,
         t: f64,
-        material: Rc<dyn Material>,
+        material: Arc<dyn Material>,
     ) -> Self {
         let front_face = dot(&r.direction, &u_out_norm) < 0.0;
         let normal = if front_face { u_out_norm } else { -u_out_norm };
@@ -32,15 +39,29 @@ impl HitRecord {
             p,
             normal,
             t,
+            u: 0.0,
+            v: 0.0,
             material,
         }
     }
+
+    pub fn with_uv(mut self, u: f64, v: f64) -> Self {
+        self.u = u;
+        self.v = v;
+        self
+    }
 }
 
 pub struct HittableList {
     pub objects: Vec<Box<dyn Hit>>,
 }
 
+impl Default for HittableList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HittableList {
     pub fn new() -> Self {
         Self { objects: vec![] }
@@ -56,7 +77,7 @@ impl HittableList {
 
 impl<T> Hit for T
 where
-    T: std::ops::Deref<Target = HittableList>,
+    T: std::ops::Deref<Target = HittableList> + Send + Sync,
 {
     fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
         let mut any_hit = None::<HitRecord>;
@@ -69,4 +90,10 @@ where
         }
         any_hit
     }
+
+    fn bounding_box(&self) -> Aabb {
+        self.objects.iter().fold(Aabb::empty(), |bbox, object| {
+            Aabb::union(&bbox, &object.bounding_box())
+        })
+    }
 }
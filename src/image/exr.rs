@@ -0,0 +1,521 @@
+use crate::film::Film;
+use crate::vec3::{Color, Vec3};
+use std::io::Write;
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+
+/// Per-channel storage type of an OpenEXR file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExrPixel {
+    /// 16 bit IEEE half float
+    Half,
+    /// 32 bit IEEE float
+    Float,
+}
+
+impl ExrPixel {
+    fn type_id(&self) -> i32 {
+        match self {
+            Self::Half => 1,
+            Self::Float => 2,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Self::Half => 2,
+            Self::Float => 4,
+        }
+    }
+}
+
+/// Rounds to the nearest half float, ties to even. Out of range values
+/// become infinity.
+pub(crate) fn f32_to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mant = bits & 0x7f_ffff;
+
+    if exp == 0xff {
+        let nan = if mant != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+    let e = exp - 127 + 15;
+    if e >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if e <= 0 {
+        // subnormal half, or too small and flushed to zero
+        if e < -10 {
+            return sign;
+        }
+        let m = mant | 0x80_0000;
+        let shift = (14 - e) as u32;
+        let half_m = m >> shift;
+        let round_bit = 1 << (shift - 1);
+        let round_up = m & round_bit != 0 && (m & (round_bit - 1) != 0 || half_m & 1 != 0);
+        return sign | (half_m + round_up as u32) as u16;
+    }
+    let half = ((e as u32) << 10) | (mant >> 13);
+    let rest = mant & 0x1fff;
+    let round_up = rest > 0x1000 || (rest == 0x1000 && half & 1 != 0);
+    // a carry out of the mantissa correctly bumps the exponent
+    sign | (half + round_up as u32) as u16
+}
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+fn box2i(width: usize, height: usize) -> Vec<u8> {
+    [0, 0, width as i32 - 1, height as i32 - 1]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+}
+
+/// Writes the film's linear radiance as an uncompressed scanline OpenEXR
+/// image with R, G and B channels.
+pub fn write_exr(mut f: impl Write, film: &Film, pixel: ExrPixel) -> Result<(), std::io::Error> {
+    let (width, height) = (film.width(), film.height());
+
+    let mut channels = Vec::new();
+    // channels are listed, and stored, in alphabetical order
+    for name in ["B", "G", "R"] {
+        channels.extend_from_slice(name.as_bytes());
+        channels.push(0);
+        channels.extend_from_slice(&pixel.type_id().to_le_bytes());
+        // pLinear and reserved bytes, then x and y sampling
+        channels.extend_from_slice(&[0, 0, 0, 0]);
+        channels.extend_from_slice(&1i32.to_le_bytes());
+        channels.extend_from_slice(&1i32.to_le_bytes());
+    }
+    channels.push(0);
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&MAGIC);
+    // version 2, single part scanline file
+    header.extend_from_slice(&2u32.to_le_bytes());
+    attribute(&mut header, "channels", "chlist", &channels);
+    attribute(&mut header, "compression", "compression", &[0]);
+    attribute(&mut header, "dataWindow", "box2i", &box2i(width, height));
+    attribute(&mut header, "displayWindow", "box2i", &box2i(width, height));
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    attribute(
+        &mut header,
+        "pixelAspectRatio",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(
+        &mut header,
+        "screenWindowWidth",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    header.push(0);
+
+    // one scanline per chunk: y, byte count, then each channel's samples
+    let line_size = 3 * width * pixel.size();
+    let chunk_size = 8 + line_size;
+    let first_chunk = header.len() + 8 * height;
+    for y in 0..height {
+        let offset = (first_chunk + y * chunk_size) as u64;
+        header.extend_from_slice(&offset.to_le_bytes());
+    }
+    f.write_all(&header)?;
+
+    let mut line = Vec::with_capacity(chunk_size);
+    for (y, row) in film.rows().enumerate() {
+        line.clear();
+        line.extend_from_slice(&(y as i32).to_le_bytes());
+        line.extend_from_slice(&(line_size as i32).to_le_bytes());
+        let channels: [fn(&Color) -> f64; 3] = [|c| c.2, |c| c.1, |c| c.0];
+        for channel in channels {
+            for color in row {
+                let v = channel(color) as f32;
+                match pixel {
+                    ExrPixel::Half => line.extend_from_slice(&f32_to_half(v).to_le_bytes()),
+                    ExrPixel::Float => line.extend_from_slice(&v.to_le_bytes()),
+                }
+            }
+        }
+        f.write_all(&line)?;
+    }
+    f.flush()
+}
+
+pub(crate) fn half_to_f32(h: u16) -> f32 {
+    let sign = ((h & 0x8000) as u32) << 16;
+    let exp = ((h >> 10) & 0x1f) as u32;
+    let mant = (h & 0x3ff) as u32;
+    let bits = match (exp, mant) {
+        (0, 0) => sign,
+        // subnormal: renormalise into an f32 exponent
+        (0, _) => {
+            let shift = mant.leading_zeros() - 21;
+            let mant = (mant << shift) & 0x3ff;
+            sign | ((127 - 15 + 1 - shift) << 23) | (mant << 13)
+        }
+        (0x1f, _) => sign | 0x7f80_0000 | (mant << 13),
+        _ => sign | ((exp + 127 - 15) << 23) | (mant << 13),
+    };
+    f32::from_bits(bits)
+}
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("exr: {msg}"))
+}
+
+struct Channel {
+    name: String,
+    /// 0 => u32, 1 => half, 2 => f32
+    pixel_type: i32,
+}
+
+impl Channel {
+    fn size(&self) -> usize {
+        if self.pixel_type == 1 { 2 } else { 4 }
+    }
+
+    fn sample(&self, bytes: &[u8]) -> f64 {
+        match self.pixel_type {
+            0 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            1 => half_to_f32(u16::from_le_bytes(bytes.try_into().unwrap())) as f64,
+            _ => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+        }
+    }
+}
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], std::io::Error> {
+        let bytes = self
+            .pos
+            .checked_add(n)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or_else(|| invalid("unexpected end of file"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    /// A byte count stored as `i32`.
+    fn size(&mut self) -> Result<usize, std::io::Error> {
+        usize::try_from(self.i32()?).map_err(|_| invalid("negative size"))
+    }
+
+    fn i32(&mut self) -> Result<i32, std::io::Error> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, std::io::Error> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, std::io::Error> {
+        let end = self.data[self.pos..]
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| invalid("unterminated string"))?;
+        let s = String::from_utf8_lossy(&self.data[self.pos..self.pos + end]).into_owned();
+        self.pos += end + 1;
+        Ok(s)
+    }
+}
+
+fn parse_channels(value: &[u8]) -> Result<Vec<Channel>, std::io::Error> {
+    let mut c = Cursor {
+        data: value,
+        pos: 0,
+    };
+    let mut channels = Vec::new();
+    loop {
+        let name = c.string()?;
+        if name.is_empty() {
+            return Ok(channels);
+        }
+        let pixel_type = c.i32()?;
+        c.take(4)?;
+        let (x_sampling, y_sampling) = (c.i32()?, c.i32()?);
+        if !(0..=2).contains(&pixel_type) {
+            return Err(invalid("unknown channel type"));
+        }
+        if x_sampling != 1 || y_sampling != 1 {
+            return Err(invalid("subsampled channels are not supported"));
+        }
+        channels.push(Channel { name, pixel_type });
+    }
+}
+
+/// Undoes the byte interleaving and delta predictor shared by the RLE and
+/// ZIP compression schemes.
+fn unpredict(mut t: Vec<u8>) -> Vec<u8> {
+    for i in 1..t.len() {
+        t[i] = t[i - 1].wrapping_add(t[i]).wrapping_sub(128);
+    }
+    let half = t.len().div_ceil(2);
+    let mut out = Vec::with_capacity(t.len());
+    for i in 0..half {
+        out.push(t[i]);
+        if half + i < t.len() {
+            out.push(t[half + i]);
+        }
+    }
+    out
+}
+
+fn rle_decompress(data: &[u8], expected: usize) -> Result<Vec<u8>, std::io::Error> {
+    let mut out = Vec::with_capacity(expected);
+    let mut i = 0;
+    while i < data.len() {
+        let count = data[i] as i8;
+        i += 1;
+        if count < 0 {
+            let n = -(count as isize) as usize;
+            out.extend_from_slice(data.get(i..i + n).ok_or_else(|| invalid("truncated rle"))?);
+            i += n;
+        } else {
+            let value = *data.get(i).ok_or_else(|| invalid("truncated rle"))?;
+            out.extend(std::iter::repeat_n(value, count as usize + 1));
+            i += 1;
+        }
+    }
+    Ok(out)
+}
+
+/// Reads a single part scanline OpenEXR image with uncompressed, RLE, ZIPS
+/// or ZIP data. `R`, `G` and `B` channels are required, except for
+/// luminance-only images with a single `Y` channel.
+pub fn read_exr(mut f: impl std::io::Read) -> Result<Film, std::io::Error> {
+    let mut data = Vec::new();
+    f.read_to_end(&mut data)?;
+    let mut c = Cursor {
+        data: &data,
+        pos: 0,
+    };
+    if c.take(4)? != MAGIC {
+        return Err(invalid("bad magic number"));
+    }
+    let version = c.i32()?;
+    if version & 0x200 != 0 {
+        return Err(invalid("tiled images are not supported"));
+    }
+    if version & 0x1800 != 0 {
+        return Err(invalid("multi-part and deep images are not supported"));
+    }
+
+    let mut channels = None;
+    let mut compression = None;
+    let mut data_window = None;
+    loop {
+        let name = c.string()?;
+        if name.is_empty() {
+            break;
+        }
+        let _kind = c.string()?;
+        let size = c.size()?;
+        let value = c.take(size)?;
+        match name.as_str() {
+            "channels" => channels = Some(parse_channels(value)?),
+            "compression" => compression = value.first().copied(),
+            "dataWindow" => {
+                let mut w = Cursor {
+                    data: value,
+                    pos: 0,
+                };
+                data_window = Some((w.i32()?, w.i32()?, w.i32()?, w.i32()?));
+            }
+            _ => {}
+        }
+    }
+    let channels = channels.ok_or_else(|| invalid("missing channels"))?;
+    let (x_min, y_min, x_max, y_max) = data_window.ok_or_else(|| invalid("missing dataWindow"))?;
+    if x_max < x_min || y_max < y_min {
+        return Err(invalid("empty data window"));
+    }
+    // widened, as the extent of a window spanning all of i32 does not fit it
+    let extent = |min: i32, max: i32| usize::try_from(max as i64 - min as i64 + 1).ok();
+    let (width, height) = extent(x_min, x_max)
+        .zip(extent(y_min, y_max))
+        .ok_or_else(|| invalid("data window too large"))?;
+    let lines_per_chunk = match compression.ok_or_else(|| invalid("missing compression"))? {
+        0..=2 => 1,
+        3 => 16,
+        _ => {
+            return Err(invalid(
+                "only NONE, RLE, ZIPS and ZIP compression are supported",
+            ));
+        }
+    };
+    let compression = compression.unwrap();
+
+    let find = |name: &str| channels.iter().position(|ch| ch.name == name);
+    let rgb = match (find("R"), find("G"), find("B"), find("Y")) {
+        (Some(r), Some(g), Some(b), _) => [r, g, b],
+        (_, _, _, Some(y)) => [y, y, y],
+        _ => return Err(invalid("no R, G, B or Y channels")),
+    };
+    let pixel_size: usize = channels.iter().map(Channel::size).sum();
+    // no scheme expands data more than deflate's 1032:1, so a window that
+    // needs more than that cannot be backed by the file
+    let line_size = width
+        .checked_mul(pixel_size)
+        .filter(|&n| {
+            n.checked_mul(height)
+                .is_some_and(|n| n / 1032 <= data.len())
+        })
+        .ok_or_else(|| invalid("data window too large"))?;
+    let offsets: Vec<usize> = (0..height.div_ceil(lines_per_chunk))
+        .map(|_| usize::try_from(c.u64()?).map_err(|_| invalid("chunk offset out of range")))
+        .collect::<Result<_, _>>()?;
+
+    let mut film = Film::new(width, height);
+    let mut sample = [0.0; 3];
+    for offset in offsets {
+        let mut chunk = Cursor {
+            data: &data,
+            pos: offset,
+        };
+        let y = chunk.i32()? as i64 - y_min as i64;
+        let size = chunk.size()?;
+        let packed = chunk.take(size)?;
+        let y = usize::try_from(y)
+            .ok()
+            .filter(|&y| y < height)
+            .ok_or_else(|| invalid("chunk outside the data window"))?;
+        let lines = lines_per_chunk.min(height - y);
+        let expected = lines * line_size;
+        let raw = if size >= expected {
+            packed.to_vec()
+        } else {
+            match compression {
+                1 => unpredict(rle_decompress(packed, expected)?),
+                2 | 3 => unpredict(super::deflate::zlib_decompress(packed)?),
+                _ => return Err(invalid("chunk size mismatch")),
+            }
+        };
+        if raw.len() != expected {
+            return Err(invalid("decompressed chunk size mismatch"));
+        }
+
+        // each line stores all of one channel's samples, then the next
+        for (line, bytes) in raw.chunks(line_size).enumerate() {
+            let mut starts = Vec::with_capacity(channels.len());
+            let mut start = 0;
+            for ch in &channels {
+                starts.push(start);
+                start += width * ch.size();
+            }
+            for i in 0..width {
+                for (k, &ch) in rgb.iter().enumerate() {
+                    let size = channels[ch].size();
+                    let at = starts[ch] + i * size;
+                    sample[k] = channels[ch].sample(&bytes[at..at + size]);
+                }
+                film.set(i, y + line, Vec3(sample[0], sample[1], sample[2]));
+            }
+        }
+    }
+    Ok(film)
+}
+
+#[cfg(test)]
+#[test]
+fn test_f32_to_half() {
+    assert_eq!(f32_to_half(0.0), 0x0000);
+    assert_eq!(f32_to_half(-0.0), 0x8000);
+    assert_eq!(f32_to_half(1.0), 0x3c00);
+    assert_eq!(f32_to_half(-2.0), 0xc000);
+    assert_eq!(f32_to_half(0.1), 0x2e66);
+    assert_eq!(f32_to_half(65504.0), 0x7bff);
+    assert_eq!(f32_to_half(1e6), 0x7c00);
+    assert_eq!(f32_to_half(f32::INFINITY), 0x7c00);
+    assert_eq!(f32_to_half(2f32.powi(-24)), 0x0001);
+    assert_eq!(f32_to_half(2f32.powi(-14)), 0x0400);
+    assert_eq!(f32_to_half(2f32.powi(-26)), 0x0000);
+}
+
+#[cfg(test)]
+#[test]
+fn test_exr_round_trip() {
+    let mut film = Film::new(5, 4);
+    for j in 0..4 {
+        for i in 0..5 {
+            film.set(i, j, Vec3(i as f64 * 100.5, j as f64 * 0.25, -1.5));
+        }
+    }
+    for pixel in [ExrPixel::Half, ExrPixel::Float] {
+        let mut encoded = Vec::new();
+        write_exr(&mut encoded, &film, pixel).unwrap();
+        assert_eq!(read_exr(encoded.as_slice()).unwrap(), film);
+    }
+    for h in [0x0001, 0x03ff, 0x0400, 0x3c00, 0x7bff, 0xc000] {
+        assert_eq!(f32_to_half(half_to_f32(h)), h);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_exr_unpredict() {
+    // bytes 0..8 interleaved as [0, 2, 4, 6, 1, 3, 5, 7] then delta coded
+    let t = vec![0, 130, 130, 130, 123, 130, 130, 130];
+    assert_eq!(unpredict(t), (0..8).collect::<Vec<u8>>());
+}
+
+#[cfg(test)]
+#[test]
+fn test_exr_corrupt() {
+    let mut encoded = Vec::new();
+    write_exr(&mut encoded, &Film::new(5, 4), ExrPixel::Half).unwrap();
+    let error = |patch: &dyn Fn(&mut Vec<u8>)| {
+        let mut data = encoded.clone();
+        patch(&mut data);
+        read_exr(data.as_slice()).err().unwrap().to_string()
+    };
+    let find = |needle: &[u8]| {
+        encoded
+            .windows(needle.len())
+            .position(|w| w == needle)
+            .unwrap()
+            + needle.len()
+    };
+
+    // the size of the first attribute, "channels"
+    let negative = |data: &mut Vec<u8>| data[24..28].copy_from_slice(&(-1i32).to_le_bytes());
+    assert_eq!(error(&negative), "exr: negative size");
+
+    let window = find(b"dataWindow\0box2i\0") + 4;
+    let wide = |data: &mut Vec<u8>| {
+        data[window..window + 4].copy_from_slice(&i32::MIN.to_le_bytes());
+        data[window + 8..window + 12].copy_from_slice(&i32::MAX.to_le_bytes());
+    };
+    assert_eq!(error(&wide), "exr: data window too large");
+    let tall = |data: &mut Vec<u8>| {
+        data[window + 4..window + 8].copy_from_slice(&i32::MIN.to_le_bytes());
+        data[window + 12..window + 16].copy_from_slice(&i32::MAX.to_le_bytes());
+    };
+    assert_eq!(error(&tall), "exr: data window too large");
+
+    // the offset table follows the header, pointing just past itself
+    let table = (0..encoded.len() - 8)
+        .find(|&i| u64::from_le_bytes(encoded[i..i + 8].try_into().unwrap()) == i as u64 + 32)
+        .unwrap();
+    let far = |data: &mut Vec<u8>| data[table..table + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert_eq!(error(&far), "exr: unexpected end of file");
+    let chunk = table + 32;
+    let negative = |data: &mut Vec<u8>| {
+        data[chunk + 4..chunk + 8].copy_from_slice(&(-1i32).to_le_bytes());
+    };
+    assert_eq!(error(&negative), "exr: negative size");
+}
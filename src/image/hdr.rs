@@ -0,0 +1,313 @@
+use crate::film::Film;
+use crate::vec3::{Color, Vec3};
+use std::io::Write;
+
+/// Shared-exponent encoding of a linear color. Negative components clamp
+/// to zero since the format cannot store them.
+fn to_rgbe(color: &Color) -> [u8; 4] {
+    let (r, g, b) = (color.0.max(0.0), color.1.max(0.0), color.2.max(0.0));
+    let v = r.max(g).max(b);
+    if v.is_nan() || v < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+    // v = m * 2^e with m in [0.5, 1)
+    let e = v.log2().floor() as i32 + 1;
+    let scale = 256.0 / 2f64.powi(e);
+    let e = e.clamp(-128, 127);
+    [
+        (r * scale).min(255.0) as u8,
+        (g * scale).min(255.0) as u8,
+        (b * scale).min(255.0) as u8,
+        (e + 128) as u8,
+    ]
+}
+
+/// Run length encodes one component plane of an adaptive RLE scanline.
+fn write_rle(out: &mut Vec<u8>, data: &[u8]) {
+    const MIN_RUN: usize = 4;
+    let mut i = 0;
+    while i < data.len() {
+        // find the next run long enough to be worth encoding
+        let mut run_start = i;
+        let mut run_len = 0;
+        while run_start < data.len() {
+            run_len = 1;
+            while run_len < 127
+                && run_start + run_len < data.len()
+                && data[run_start + run_len] == data[run_start]
+            {
+                run_len += 1;
+            }
+            if run_len >= MIN_RUN {
+                break;
+            }
+            run_start += run_len;
+        }
+        // literals up to the run
+        while i < run_start {
+            let n = (run_start - i).min(128);
+            out.push(n as u8);
+            out.extend_from_slice(&data[i..i + n]);
+            i += n;
+        }
+        if run_len >= MIN_RUN && run_start < data.len() {
+            out.push(128 + run_len as u8);
+            out.push(data[run_start]);
+            i = run_start + run_len;
+        }
+    }
+}
+
+/// Writes the film as an adaptive run length encoded Radiance RGBE image.
+pub fn write_hdr(mut f: impl Write, film: &Film) -> Result<(), std::io::Error> {
+    let width = film.width();
+    write!(
+        f,
+        "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+        film.height(),
+        width
+    )?;
+    let mut line = Vec::with_capacity(4 * width + 4);
+    let mut planes: [Vec<u8>; 4] = std::array::from_fn(|_| Vec::with_capacity(width));
+    for row in film.rows() {
+        line.clear();
+        // the RLE form only exists for widths in [8, 32767]
+        if !(8..=0x7fff).contains(&width) {
+            for color in row {
+                line.extend_from_slice(&to_rgbe(color));
+            }
+        } else {
+            for plane in planes.iter_mut() {
+                plane.clear();
+            }
+            for color in row {
+                for (plane, byte) in planes.iter_mut().zip(to_rgbe(color)) {
+                    plane.push(byte);
+                }
+            }
+            line.extend_from_slice(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8]);
+            for plane in &planes {
+                write_rle(&mut line, plane);
+            }
+        }
+        f.write_all(&line)?;
+    }
+    f.flush()
+}
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("hdr: {msg}"))
+}
+
+fn from_rgbe(rgbe: &[u8]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::zero();
+    }
+    let f = 2f64.powi(rgbe[3] as i32 - (128 + 8));
+    Vec3(
+        (rgbe[0] as f64 + 0.5) * f,
+        (rgbe[1] as f64 + 0.5) * f,
+        (rgbe[2] as f64 + 0.5) * f,
+    )
+}
+
+/// Reads one scanline of RGBE quads, in any of the flat, old style run
+/// length or adaptive run length forms.
+fn read_scanline(
+    data: &[u8],
+    pos: &mut usize,
+    width: usize,
+    out: &mut Vec<u8>,
+) -> Result<(), std::io::Error> {
+    let byte = |pos: &mut usize| -> Result<u8, std::io::Error> {
+        let b = *data
+            .get(*pos)
+            .ok_or_else(|| invalid("truncated pixel data"))?;
+        *pos += 1;
+        Ok(b)
+    };
+    out.clear();
+
+    let adaptive = (8..=0x7fff).contains(&width)
+        && data.get(*pos..*pos + 2) == Some(&[2, 2])
+        && data.get(*pos + 2).is_some_and(|&b| b & 0x80 == 0);
+    if adaptive {
+        let encoded_width = (data[*pos + 2] as usize) << 8 | data[*pos + 3] as usize;
+        if encoded_width != width {
+            return Err(invalid("scanline width mismatch"));
+        }
+        *pos += 4;
+        let mut planes = vec![0u8; 4 * width];
+        for plane in planes.chunks_mut(width) {
+            let mut i = 0;
+            while i < width {
+                let count = byte(pos)? as usize;
+                if count > 128 {
+                    let n = count - 128;
+                    let value = byte(pos)?;
+                    plane
+                        .get_mut(i..i + n)
+                        .ok_or_else(|| invalid("run overflows scanline"))?
+                        .fill(value);
+                    i += n;
+                } else {
+                    if count == 0 || i + count > width {
+                        return Err(invalid("bad literal run"));
+                    }
+                    for v in &mut plane[i..i + count] {
+                        *v = byte(pos)?;
+                    }
+                    i += count;
+                }
+            }
+        }
+        for i in 0..width {
+            out.extend_from_slice(&[
+                planes[i],
+                planes[width + i],
+                planes[2 * width + i],
+                planes[3 * width + i],
+            ]);
+        }
+        return Ok(());
+    }
+
+    // flat quads, where (1, 1, 1, n) repeats the previous pixel
+    let mut shift = 0;
+    while out.len() < 4 * width {
+        let quad = [byte(pos)?, byte(pos)?, byte(pos)?, byte(pos)?];
+        if quad[..3] == [1, 1, 1] {
+            let prev: [u8; 4] = out
+                .get(out.len().wrapping_sub(4)..)
+                .and_then(|p| p.try_into().ok())
+                .ok_or_else(|| invalid("repeat with no previous pixel"))?;
+            // runs of more than 2^32 pixels are beyond any image we read
+            if shift > 24 {
+                return Err(invalid("run overflows scanline"));
+            }
+            let n = (quad[3] as usize) << shift;
+            if out.len() + 4 * n > 4 * width {
+                return Err(invalid("run overflows scanline"));
+            }
+            for _ in 0..n {
+                out.extend_from_slice(&prev);
+            }
+            shift += 8;
+        } else {
+            out.extend_from_slice(&quad);
+            shift = 0;
+        }
+    }
+    Ok(())
+}
+
+/// Reads a Radiance RGBE image into linear radiance.
+pub fn read_hdr(mut f: impl std::io::Read) -> Result<Film, std::io::Error> {
+    let mut data = Vec::new();
+    f.read_to_end(&mut data)?;
+    if !data.starts_with(b"#?") {
+        return Err(invalid("missing #? signature"));
+    }
+
+    // header lines up to a blank line, then the resolution line
+    let mut pos = 0;
+    let mut line = || -> Result<&[u8], std::io::Error> {
+        let end = data[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| invalid("truncated header"))?;
+        let l = &data[pos..pos + end];
+        pos += end + 1;
+        Ok(l)
+    };
+    loop {
+        let l = line()?;
+        if l.is_empty() {
+            break;
+        }
+        if let Some(format) = l.strip_prefix(b"FORMAT=")
+            && format != b"32-bit_rle_rgbe"
+        {
+            return Err(invalid("only 32-bit_rle_rgbe is supported"));
+        }
+    }
+    let resolution = String::from_utf8_lossy(line()?).into_owned();
+    let fields: Vec<&str> = resolution.split_whitespace().collect();
+    let (flip_y, height, width) = match fields.as_slice() {
+        ["-Y", h, "+X", w] => (false, h, w),
+        ["+Y", h, "+X", w] => (true, h, w),
+        _ => return Err(invalid("unsupported resolution line")),
+    };
+    let parse = |s: &str| s.parse::<usize>().map_err(|_| invalid("bad resolution"));
+    let (width, height) = (parse(width)?, parse(height)?);
+    // adaptive RLE packs at most 127 bytes into 2, about 16 pixels a byte;
+    // old-style repeat runs can pack more, so allow some slack over that
+    if width
+        .checked_mul(height)
+        .is_none_or(|pixels| pixels / 64 > data.len() - pos)
+    {
+        return Err(invalid("resolution too large"));
+    }
+
+    let mut film = Film::new(width, height);
+    let mut scanline = Vec::with_capacity(4 * width);
+    for y in 0..height {
+        read_scanline(&data, &mut pos, width, &mut scanline)?;
+        let j = if flip_y { height - 1 - y } else { y };
+        for (i, rgbe) in scanline.chunks(4).enumerate() {
+            film.set(i, j, from_rgbe(rgbe));
+        }
+    }
+    Ok(film)
+}
+
+#[cfg(test)]
+#[test]
+fn test_rgbe() {
+    assert_eq!(to_rgbe(&Color::zero()), [0, 0, 0, 0]);
+    assert_eq!(to_rgbe(&Color::one()), [128, 128, 128, 129]);
+    assert_eq!(to_rgbe(&Vec3(0.5, 0.25, 0.0)), [128, 64, 0, 128]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_hdr_round_trip() {
+    let mut film = Film::new(20, 3);
+    for j in 0..3 {
+        for i in 0..20 {
+            // runs on the left, noise on the right
+            let v = if i < 10 {
+                3.0
+            } else {
+                (i * 7 + j) as f64 * 0.37
+            };
+            film.set(i, j, Vec3(v, 0.5 * v, 0.01));
+        }
+    }
+    let mut encoded = Vec::new();
+    write_hdr(&mut encoded, &film).unwrap();
+    let decoded = read_hdr(encoded.as_slice()).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (20, 3));
+    for (a, b) in film.pixels().iter().zip(decoded.pixels()) {
+        // one part in 256 of the largest component
+        assert!((*a - *b).len() <= a.0 / 128.0);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_hdr_corrupt() {
+    let error = |data: &[u8]| read_hdr(data).err().unwrap().to_string();
+    let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n";
+    let huge = [&header[..], b"-Y 99999999999 +X 99999999999\n"].concat();
+    assert_eq!(error(&huge), "hdr: resolution too large");
+    let unbacked = [&header[..], b"-Y 1000 +X 1000\n\x80\x80\x80\x80"].concat();
+    assert_eq!(error(&unbacked), "hdr: resolution too large");
+
+    // empty (1, 1, 1, 0) repeats shift later counts past any run length
+    let mut runs = [&header[..], b"-Y 1 +X 100\n\x80\x80\x80\x80"].concat();
+    for _ in 0..10 {
+        runs.extend([1, 1, 1, 0]);
+    }
+    runs.resize(runs.len() + 64, 0);
+    assert_eq!(error(&runs), "hdr: run overflows scanline");
+}
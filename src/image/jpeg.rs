@@ -0,0 +1,30 @@
+use crate::color::srgb_to_linear;
+use crate::film::Film;
+use crate::vec3::Vec3;
+use zune_jpeg::JpegDecoder;
+use zune_jpeg::zune_core::bytestream::ZCursor;
+use zune_jpeg::zune_core::colorspace::ColorSpace;
+use zune_jpeg::zune_core::options::DecoderOptions;
+
+/// Reads a baseline or progressive JPEG, returning linear colors.
+pub fn read_jpeg(mut f: impl std::io::Read) -> Result<Film, std::io::Error> {
+    let mut data = Vec::new();
+    f.read_to_end(&mut data)?;
+    let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::RGB);
+    let mut decoder = JpegDecoder::new_with_options(ZCursor::new(&data), options);
+    let rgb = decoder.decode().map_err(|e| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, format!("jpeg: {e:?}"))
+    })?;
+    let info = decoder.info().expect("headers are decoded by decode()");
+
+    let linear = |b: u8| srgb_to_linear(b as f64 / 255.0);
+    let pixels = rgb
+        .chunks(3)
+        .map(|c| Vec3(linear(c[0]), linear(c[1]), linear(c[2])))
+        .collect();
+    Ok(Film::from_pixels(
+        info.width as usize,
+        info.height as usize,
+        pixels,
+    ))
+}
@@ -0,0 +1,106 @@
+//! Encoders that write a rendered `Film` out to image files, and decoders
+//! that read images back in as linear `Film`s.
+
+mod deflate;
+mod exr;
+mod hdr;
+mod jpeg;
+mod png;
+mod ppm;
+
+pub use exr::{ExrPixel, read_exr, write_exr};
+pub use hdr::{read_hdr, write_hdr};
+pub use jpeg::read_jpeg;
+pub use png::{PngDepth, read_png, write_png};
+pub use ppm::read_ppm;
+
+use crate::color::write_ppm;
+use crate::film::Film;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// ASCII P3 portable pixmap
+    Ppm,
+    /// 8 bit sRGB PNG
+    Png,
+    /// 16 bit sRGB PNG
+    Png16,
+    /// Radiance RGBE, unclamped linear radiance
+    Hdr,
+    /// OpenEXR with half float channels, unclamped linear radiance
+    Exr,
+    /// OpenEXR with full float channels
+    ExrFloat,
+}
+
+impl ImageFormat {
+    /// Guesses the format from a file extension. `.png` maps to 8 bit and
+    /// `.exr` to half floats.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "ppm" => Some(Self::Ppm),
+            "png" => Some(Self::Png),
+            "hdr" => Some(Self::Hdr),
+            "exr" => Some(Self::Exr),
+            _ => None,
+        }
+    }
+}
+
+pub fn write_image(f: impl Write, film: &Film, format: ImageFormat) -> Result<(), std::io::Error> {
+    match format {
+        ImageFormat::Ppm => write_ppm(f, film),
+        ImageFormat::Png => write_png(f, film, PngDepth::Eight),
+        ImageFormat::Png16 => write_png(f, film, PngDepth::Sixteen),
+        ImageFormat::Hdr => write_hdr(f, film),
+        ImageFormat::Exr => write_exr(f, film, ExrPixel::Half),
+        ImageFormat::ExrFloat => write_exr(f, film, ExrPixel::Float),
+    }
+}
+
+/// Writes the film to `path`, picking the format from its extension.
+pub fn save(path: impl AsRef<Path>, film: &Film) -> Result<(), std::io::Error> {
+    let format = ImageFormat::from_path(&path).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unknown image format: {}", path.as_ref().display()),
+        )
+    })?;
+    save_as(path, film, format)
+}
+
+/// Writes the film to `path` in `format`, for the formats an extension
+/// cannot pick, like 16 bit PNG.
+pub fn save_as(
+    path: impl AsRef<Path>,
+    film: &Film,
+    format: ImageFormat,
+) -> Result<(), std::io::Error> {
+    let file = std::fs::File::create(path)?;
+    write_image(std::io::BufWriter::new(file), film, format)
+}
+
+/// Reads the image at `path` into linear color, picking the decoder from
+/// its extension. 8 and 16 bit formats are taken to be sRGB encoded.
+pub fn load(path: impl AsRef<Path>) -> Result<Film, std::io::Error> {
+    let path = path.as_ref();
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    match ext.as_deref() {
+        Some("hdr") => read_hdr(file),
+        Some("exr") => read_exr(file),
+        Some("png") => read_png(file),
+        Some("ppm" | "pgm" | "pnm") => read_ppm(file),
+        Some("jpg" | "jpeg") => read_jpeg(file),
+        _ => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unknown image format: {}", path.display()),
+        )),
+    }
+}
@@ -0,0 +1,381 @@
+use super::deflate::{zlib_compress, zlib_decompress};
+use crate::color::{linear_to_srgb, quantize, srgb_to_linear};
+use crate::film::Film;
+use crate::vec3::Vec3;
+use std::io::Write;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PngDepth {
+    Eight,
+    Sixteen,
+}
+
+fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+    table
+}
+
+pub(crate) fn crc32(chunks: &[&[u8]]) -> u32 {
+    let table = crc32_table();
+    let mut c = 0xffffffffu32;
+    for chunk in chunks {
+        for &byte in *chunk {
+            c = table[((c ^ byte as u32) & 0xff) as usize] ^ (c >> 8);
+        }
+    }
+    c ^ 0xffffffff
+}
+
+fn write_chunk(f: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> Result<(), std::io::Error> {
+    f.write_all(&(data.len() as u32).to_be_bytes())?;
+    f.write_all(kind)?;
+    f.write_all(data)?;
+    f.write_all(&crc32(&[kind, data]).to_be_bytes())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Filters one scanline with each PNG filter type and appends the one with
+/// the smallest sum of absolute residuals, the heuristic libpng uses.
+fn filter_row(out: &mut Vec<u8>, row: &[u8], prior: &[u8], bpp: usize) {
+    let mut best: Option<(u64, u8, Vec<u8>)> = None;
+    for filter in 0..5u8 {
+        let filtered: Vec<u8> = (0..row.len())
+            .map(|i| {
+                let a = if i >= bpp { row[i - bpp] } else { 0 };
+                let b = prior[i];
+                let c = if i >= bpp { prior[i - bpp] } else { 0 };
+                let predicted = match filter {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    _ => paeth(a, b, c),
+                };
+                row[i].wrapping_sub(predicted)
+            })
+            .collect();
+        let cost = filtered
+            .iter()
+            .map(|&v| (v as i8).unsigned_abs() as u64)
+            .sum();
+        if best
+            .as_ref()
+            .is_none_or(|(best_cost, _, _)| cost < *best_cost)
+        {
+            best = Some((cost, filter, filtered));
+        }
+    }
+    let (_, filter, filtered) = best.unwrap();
+    out.push(filter);
+    out.extend_from_slice(&filtered);
+}
+
+/// Writes the film as an sRGB encoded, 8 or 16 bit per channel RGB PNG.
+pub fn write_png(mut f: impl Write, film: &Film, depth: PngDepth) -> Result<(), std::io::Error> {
+    let (bit_depth, max) = match depth {
+        PngDepth::Eight => (8u8, 255),
+        PngDepth::Sixteen => (16u8, 65535),
+    };
+    let bpp = 3 * bit_depth as usize / 8;
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&(film.width() as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(film.height() as u32).to_be_bytes());
+    // truecolor, deflate, adaptive filtering, no interlace
+    ihdr.extend_from_slice(&[bit_depth, 2, 0, 0, 0]);
+
+    let stride = film.width() * bpp;
+    let mut raw = Vec::with_capacity((stride + 1) * film.height());
+    let mut prior = vec![0u8; stride];
+    let mut row = Vec::with_capacity(stride);
+    for pixels in film.rows() {
+        row.clear();
+        for color in pixels {
+            for c in [color.0, color.1, color.2] {
+                let v = quantize(linear_to_srgb(c), max);
+                match depth {
+                    PngDepth::Eight => row.push(v as u8),
+                    PngDepth::Sixteen => row.extend_from_slice(&(v as u16).to_be_bytes()),
+                }
+            }
+        }
+        filter_row(&mut raw, &row, &prior, bpp);
+        std::mem::swap(&mut prior, &mut row);
+    }
+
+    f.write_all(&SIGNATURE)?;
+    write_chunk(&mut f, b"IHDR", &ihdr)?;
+    // rendering intent: perceptual
+    write_chunk(&mut f, b"sRGB", &[0])?;
+    write_chunk(&mut f, b"IDAT", &zlib_compress(&raw))?;
+    write_chunk(&mut f, b"IEND", &[])?;
+    f.flush()
+}
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("png: {msg}"))
+}
+
+/// Reverses the scanline filter in place, given the already unfiltered
+/// previous line.
+fn unfilter_row(
+    filter: u8,
+    row: &mut [u8],
+    prior: &[u8],
+    bpp: usize,
+) -> Result<(), std::io::Error> {
+    for i in 0..row.len() {
+        let a = if i >= bpp { row[i - bpp] } else { 0 };
+        let b = prior[i];
+        let c = if i >= bpp { prior[i - bpp] } else { 0 };
+        let predicted = match filter {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => ((a as u16 + b as u16) / 2) as u8,
+            4 => paeth(a, b, c),
+            _ => return Err(invalid("unknown filter type")),
+        };
+        row[i] = row[i].wrapping_add(predicted);
+    }
+    Ok(())
+}
+
+/// sample `n` of a packed scanline holding `depth` bit samples
+fn sample(row: &[u8], n: usize, depth: u8) -> u16 {
+    match depth {
+        16 => u16::from_be_bytes([row[2 * n], row[2 * n + 1]]),
+        8 => row[n] as u16,
+        _ => {
+            let bit = n * depth as usize;
+            let shift = 8 - depth as usize - bit % 8;
+            ((row[bit / 8] >> shift) & ((1 << depth) - 1)) as u16
+        }
+    }
+}
+
+/// Reads a PNG of any color type, bit depth and interlacing, returning
+/// linear colors. Samples are taken as sRGB encoded; alpha is dropped.
+pub fn read_png(mut f: impl std::io::Read) -> Result<Film, std::io::Error> {
+    let mut data = Vec::new();
+    f.read_to_end(&mut data)?;
+    if !data.starts_with(&SIGNATURE) {
+        return Err(invalid("missing signature"));
+    }
+
+    let mut header = None;
+    let mut palette = Vec::new();
+    let mut idat = Vec::new();
+    let mut pos = SIGNATURE.len();
+    loop {
+        let len_bytes = data
+            .get(pos..pos + 8)
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        let len = u32::from_be_bytes(len_bytes[..4].try_into().unwrap()) as usize;
+        let kind = &data[pos + 4..pos + 8];
+        let body = data
+            .get(pos + 8..pos + 8 + len)
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        let crc = data
+            .get(pos + 8 + len..pos + 12 + len)
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        if crc32(&[kind, body]).to_be_bytes() != crc {
+            return Err(invalid("chunk crc mismatch"));
+        }
+        pos += 12 + len;
+        match kind {
+            b"IHDR" if body.len() == 13 => {
+                let width = u32::from_be_bytes(body[..4].try_into().unwrap()) as usize;
+                let height = u32::from_be_bytes(body[4..8].try_into().unwrap()) as usize;
+                // bit depth, color type, interlace method
+                header = Some((width, height, body[8], body[9], body[12] == 1));
+            }
+            b"PLTE" => {
+                let entries = body.chunks_exact(3);
+                if !entries.remainder().is_empty() {
+                    return Err(invalid("bad PLTE"));
+                }
+                palette = entries.map(|c| [c[0], c[1], c[2]]).collect();
+            }
+            b"IDAT" => idat.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {}
+        }
+    }
+    let (width, height, depth, color_type, interlaced) =
+        header.ok_or_else(|| invalid("missing IHDR"))?;
+    let channels = match (color_type, depth) {
+        (0, 1 | 2 | 4 | 8 | 16) => 1,
+        (2, 8 | 16) => 3,
+        (3, 1 | 2 | 4 | 8) => 1,
+        (4, 8 | 16) => 2,
+        (6, 8 | 16) => 4,
+        _ => return Err(invalid("bad color type and bit depth")),
+    };
+    if color_type == 3 && palette.is_empty() {
+        return Err(invalid("missing PLTE"));
+    }
+
+    let max = ((1u32 << depth) - 1) as f64;
+    let to_color = |s: &[u16]| -> Result<Vec3, std::io::Error> {
+        let linear = |v: u16| srgb_to_linear(v as f64 / max);
+        Ok(match color_type {
+            0 | 4 => Vec3(linear(s[0]), linear(s[0]), linear(s[0])),
+            3 => {
+                let [r, g, b] = *palette
+                    .get(s[0] as usize)
+                    .ok_or_else(|| invalid("palette index out of range"))?;
+                let linear = |v: u8| srgb_to_linear(v as f64 / 255.0);
+                Vec3(linear(r), linear(g), linear(b))
+            }
+            _ => Vec3(linear(s[0]), linear(s[1]), linear(s[2])),
+        })
+    };
+
+    // Adam7 passes as (x0, y0, dx, dy), or the whole image at once
+    let passes: &[(usize, usize, usize, usize)] = if interlaced {
+        &[
+            (0, 0, 8, 8),
+            (4, 0, 8, 8),
+            (0, 4, 4, 8),
+            (2, 0, 4, 4),
+            (0, 2, 2, 4),
+            (1, 0, 2, 2),
+            (0, 1, 1, 2),
+        ]
+    } else {
+        &[(0, 0, 1, 1)]
+    };
+    let raw = zlib_decompress(&idat)?;
+    // every pixel takes at least one bit of the image data
+    if width
+        .checked_mul(height)
+        .is_none_or(|pixels| pixels / 8 > raw.len())
+    {
+        return Err(invalid("image size does not match its data"));
+    }
+    let bpp = (channels * depth as usize).div_ceil(8);
+    let mut film = Film::new(width, height);
+    let mut samples = [0u16; 4];
+    let mut offset = 0;
+    for &(x0, y0, dx, dy) in passes {
+        let pass_width = (width + dx - 1 - x0) / dx;
+        let pass_height = (height + dy - 1 - y0) / dy;
+        if pass_width == 0 || pass_height == 0 {
+            continue;
+        }
+        let stride = (pass_width * channels * depth as usize).div_ceil(8);
+        let mut prior = vec![0u8; stride];
+        for j in 0..pass_height {
+            let line = raw
+                .get(offset..offset + 1 + stride)
+                .ok_or_else(|| invalid("truncated image data"))?;
+            offset += 1 + stride;
+            let mut row = line[1..].to_vec();
+            unfilter_row(line[0], &mut row, &prior, bpp)?;
+            for i in 0..pass_width {
+                for (c, s) in samples[..channels].iter_mut().enumerate() {
+                    *s = sample(&row, i * channels + c, depth);
+                }
+                film.set(x0 + i * dx, y0 + j * dy, to_color(&samples)?);
+            }
+            prior = row;
+        }
+    }
+    Ok(film)
+}
+
+#[cfg(test)]
+#[test]
+fn test_crc32() {
+    assert_eq!(crc32(&[b"IEND"]), 0xae426082);
+    assert_eq!(crc32(&[b"123", b"456789"]), 0xcbf43926);
+}
+
+#[cfg(test)]
+#[test]
+fn test_png_round_trip() {
+    use crate::color::linear_to_srgb;
+
+    let mut film = Film::new(7, 5);
+    for j in 0..5 {
+        for i in 0..7 {
+            film.set(i, j, Vec3(i as f64 / 6.0, j as f64 / 4.0, 0.5));
+        }
+    }
+    for (depth, levels) in [(PngDepth::Eight, 255.0), (PngDepth::Sixteen, 65535.0)] {
+        let mut encoded = Vec::new();
+        write_png(&mut encoded, &film, depth).unwrap();
+        let decoded = read_png(encoded.as_slice()).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (7, 5));
+        for (a, b) in film.pixels().iter().zip(decoded.pixels()) {
+            for (x, y) in [(a.0, b.0), (a.1, b.1), (a.2, b.2)] {
+                // within half a quantization step once re-encoded
+                assert!((linear_to_srgb(x) - linear_to_srgb(y)).abs() <= 0.5 / levels + 1e-9);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_png_corrupt() {
+    let png = |chunks: &[(&[u8; 4], &[u8])]| {
+        let mut data = SIGNATURE.to_vec();
+        for (kind, body) in chunks {
+            write_chunk(&mut data, kind, body).unwrap();
+        }
+        data
+    };
+    let error = |data: Vec<u8>| read_png(data.as_slice()).err().unwrap().to_string();
+    let header = |width: u32, height: u32, color_type: u8| {
+        let mut ihdr = width.to_be_bytes().to_vec();
+        ihdr.extend(height.to_be_bytes());
+        ihdr.extend([8, color_type, 0, 0, 0]);
+        ihdr
+    };
+    // one gray pixel, unfiltered
+    let idat = super::deflate::zlib_compress(&[0, 0]);
+
+    let ihdr = header(1, 1, 3);
+    let palette = png(&[(b"IHDR", &ihdr), (b"PLTE", &[1, 2, 3, 4]), (b"IEND", &[])]);
+    assert_eq!(error(palette), "png: bad PLTE");
+
+    let ihdr = header(u32::MAX, u32::MAX, 0);
+    let huge = png(&[(b"IHDR", &ihdr), (b"IDAT", &idat), (b"IEND", &[])]);
+    assert_eq!(error(huge), "png: image size does not match its data");
+
+    // the same one pixel image reads fine with an honest header
+    let ihdr = header(1, 1, 0);
+    let tiny = png(&[(b"IHDR", &ihdr), (b"IDAT", &idat), (b"IEND", &[])]);
+    assert_eq!(
+        read_png(tiny.as_slice()).unwrap().get(0, 0),
+        Vec3(0., 0., 0.)
+    );
+}
@@ -0,0 +1,137 @@
+use crate::color::srgb_to_linear;
+use crate::film::Film;
+use crate::vec3::Vec3;
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("ppm: {msg}"))
+}
+
+/// Splits off the next whitespace separated header token, skipping
+/// `#` comments.
+fn token<'a>(data: &'a [u8], pos: &mut usize) -> Result<&'a [u8], std::io::Error> {
+    loop {
+        match data.get(*pos) {
+            Some(b'#') => {
+                while data.get(*pos).is_some_and(|&b| b != b'\n') {
+                    *pos += 1;
+                }
+            }
+            Some(b) if b.is_ascii_whitespace() => *pos += 1,
+            Some(_) => break,
+            None => return Err(invalid("truncated header")),
+        }
+    }
+    let start = *pos;
+    while data.get(*pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+        *pos += 1;
+    }
+    Ok(&data[start..*pos])
+}
+
+fn number(data: &[u8], pos: &mut usize) -> Result<u32, std::io::Error> {
+    std::str::from_utf8(token(data, pos)?)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid("bad number"))
+}
+
+/// Reads an ASCII (`P2`, `P3`) or binary (`P5`, `P6`) portable graymap or
+/// pixmap. Samples are taken as sRGB encoded and returned linear.
+pub fn read_ppm(mut f: impl std::io::Read) -> Result<Film, std::io::Error> {
+    let mut data = Vec::new();
+    f.read_to_end(&mut data)?;
+    let mut pos = 0;
+    let magic = token(&data, &mut pos)?;
+    let (channels, binary) = match magic {
+        b"P2" => (1, false),
+        b"P3" => (3, false),
+        b"P5" => (1, true),
+        b"P6" => (3, true),
+        _ => return Err(invalid("unsupported magic number")),
+    };
+    let width = number(&data, &mut pos)? as usize;
+    let height = number(&data, &mut pos)? as usize;
+    let max = number(&data, &mut pos)?;
+    if max == 0 || max > 65535 {
+        return Err(invalid("bad maximum value"));
+    }
+
+    let count = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(channels))
+        .ok_or_else(|| invalid("image too large"))?;
+    let samples: Vec<u32> = if binary {
+        // exactly one whitespace byte separates the header from the raster
+        pos += 1;
+        let size = if max > 255 { 2 } else { 1 };
+        let raster = count
+            .checked_mul(size)
+            .and_then(|n| data.get(pos..pos.checked_add(n)?))
+            .ok_or_else(|| invalid("truncated raster"))?;
+        if size == 2 {
+            raster
+                .chunks(2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]) as u32)
+                .collect()
+        } else {
+            raster.iter().map(|&b| b as u32).collect()
+        }
+    } else {
+        (0..count)
+            .map(|_| number(&data, &mut pos))
+            .collect::<Result<_, _>>()?
+    };
+
+    let scale = 1.0 / max as f64;
+    let linear = |s: u32| srgb_to_linear(s.min(max) as f64 * scale);
+    let pixels = samples
+        .chunks(channels)
+        .map(|c| match c {
+            [g] => Vec3(linear(*g), linear(*g), linear(*g)),
+            _ => Vec3(linear(c[0]), linear(c[1]), linear(c[2])),
+        })
+        .collect();
+    Ok(Film::from_pixels(width, height, pixels))
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_ppm() {
+    use crate::color::{linear_to_srgb, write_ppm};
+
+    // the same 2x1 image as ASCII and as 16 bit binary, with maxval 1000
+    let ascii = b"P3\n# two pixels\n2 1\n1000\n0 500 1000\n1000 1000 250\n";
+    let mut binary = b"P6 2 1 1000\n".to_vec();
+    for v in [0u16, 500, 1000, 1000, 1000, 250] {
+        binary.extend(v.to_be_bytes());
+    }
+    for data in [&ascii[..], &binary] {
+        let film = read_ppm(data).unwrap();
+        assert_eq!((film.width(), film.height()), (2, 1));
+        let encoded = film.get(0, 0);
+        assert_eq!(encoded.0, 0.0);
+        assert!((linear_to_srgb(encoded.1) - 0.5).abs() < 1e-12);
+        assert!((linear_to_srgb(film.get(1, 0).2) - 0.25).abs() < 1e-12);
+    }
+
+    // what write_ppm stores reads back within one 8 bit step
+    let mut film = Film::new(3, 2);
+    film.set(1, 0, Vec3(0.2, 0.5, 1.0));
+    film.set(2, 1, Vec3(0.01, 2.0, -1.0));
+    let mut encoded = Vec::new();
+    write_ppm(&mut encoded, &film).unwrap();
+    let decoded = read_ppm(encoded.as_slice()).unwrap();
+    for (a, b) in film.pixels().iter().zip(decoded.pixels()) {
+        for (x, y) in [(a.0, b.0), (a.1, b.1), (a.2, b.2)] {
+            let x = linear_to_srgb(x).clamp(0.0, 1.0);
+            assert!((x - linear_to_srgb(y)).abs() <= 0.5 / 255.0 + 1e-9);
+        }
+    }
+
+    let err = read_ppm(&b"P6 4294967295 4294967295 255\n"[..])
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "ppm: image too large");
+    let err = read_ppm(&b"P3 2 1 255\n0 0 0\n"[..]).err().unwrap();
+    assert_eq!(err.to_string(), "ppm: truncated header");
+}
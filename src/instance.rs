@@ -0,0 +1,229 @@
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, HitRecord};
+use crate::interval::Interval;
+use crate::math::{AnimatedTransform, Transform};
+use crate::ray::Ray;
+use crate::vec3::{Vec3, unit_vector};
+use std::sync::Arc;
+
+/// Places a shared object in the scene with its own transform.
+///
+/// Rays are moved into the object's space rather than the object into the
+/// world, so one mesh can be instanced any number of times while its
+/// triangles are stored once.
+pub struct Instance {
+    object: Arc<dyn Hit>,
+    transform: Transform,
+    /// applied before `transform`, at the time of each ray
+    motion: Option<AnimatedTransform>,
+    bbox: Aabb,
+}
+
+impl Instance {
+    pub fn new(object: Arc<dyn Hit>, transform: Transform) -> Self {
+        Self::build(object, transform, None)
+    }
+
+    pub fn obj(object: Arc<dyn Hit>, transform: Transform) -> Box<dyn Hit> {
+        Box::new(Self::new(object, transform))
+    }
+
+    /// An instance that moves with time. Transforms added afterwards with
+    /// `then` and friends apply on top of the animation.
+    pub fn animated(object: Arc<dyn Hit>, motion: AnimatedTransform) -> Self {
+        Self::build(object, Transform::identity(), Some(motion))
+    }
+
+    fn build(
+        object: Arc<dyn Hit>,
+        transform: Transform,
+        motion: Option<AnimatedTransform>,
+    ) -> Self {
+        let bbox = match &motion {
+            Some(motion) => motion.bounding_box(&object.bounding_box()),
+            None => object.bounding_box(),
+        };
+        Self {
+            bbox: transform.bounding_box(&bbox),
+            object,
+            transform,
+            motion,
+        }
+    }
+
+    /// Applies `transform` after the current one.
+    pub fn then(self, transform: Transform) -> Self {
+        Self::build(self.object, transform * self.transform, self.motion)
+    }
+
+    pub fn translate(self, offset: Vec3) -> Self {
+        self.then(Transform::translate(offset))
+    }
+
+    pub fn rotate(self, axis: &Vec3, degrees: f64) -> Self {
+        self.then(Transform::rotate(axis, degrees))
+    }
+
+    pub fn scale(self, factors: Vec3) -> Self {
+        self.then(Transform::scale(factors))
+    }
+
+    /// Object to world transform at `time`.
+    pub fn transform(&self, time: f64) -> Transform {
+        match &self.motion {
+            Some(motion) => self.transform * motion.at(time),
+            None => self.transform,
+        }
+    }
+}
+
+impl Hit for Instance {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let transform = self.transform(r.time);
+        // the direction is not renormalized, so t means the same in both spaces
+        let to_object = transform.inverse();
+        let local = Ray {
+            origin: to_object.point(&r.origin),
+            direction: to_object.vector(&r.direction),
+            time: r.time,
+            wavelength: r.wavelength,
+        };
+        let mut rec = self.object.hit(&local, ray_t)?;
+        rec.p = transform.point(&rec.p);
+        // the normal already faces the ray, and the inverse transpose keeps
+        // the sign of its dot product with the transformed direction
+        rec.normal = unit_vector(&transform.normal(&rec.normal));
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_instance_hit() {
+    use crate::material::Lambertian;
+    use crate::sphere::Sphere;
+
+    let material = Lambertian::obj(Vec3(0.5, 0.5, 0.5));
+    let ball: Arc<dyn Hit> = Arc::from(Sphere::obj(Vec3::zero(), 1.0, material));
+    let ellipsoid = Instance::new(Arc::clone(&ball), Transform::identity())
+        .scale(Vec3(2., 1., 1.))
+        .rotate(&Vec3(0., 1., 0.), 90.)
+        .translate(Vec3(0., 0., -10.));
+
+    // the long axis now points along z, so the front is at z = -8
+    let r = Ray {
+        origin: Vec3::zero(),
+        direction: Vec3(0., 0., -1.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let rec = ellipsoid
+        .hit(&r, &Interval::new(0.001, f64::INFINITY))
+        .unwrap();
+    assert!((rec.t - 8.0).abs() < 1e-9);
+    assert!((rec.p - Vec3(0., 0., -8.)).len() < 1e-9);
+    assert!((rec.normal - Vec3(0., 0., 1.)).len() < 1e-9);
+    assert!(rec.front_face);
+
+    // off-axis normals follow the stretched surface, not the unit sphere
+    let r = Ray {
+        origin: Vec3(0.5, 0., 0.),
+        direction: Vec3(0., 0., -1.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let rec = ellipsoid
+        .hit(&r, &Interval::new(0.001, f64::INFINITY))
+        .unwrap();
+    // gradient of x² + y² + ((z + 10) / 2)² at the hit point
+    let gradient = unit_vector(&Vec3(2. * rec.p.0, 0., (rec.p.2 + 10.) / 2.));
+    assert!((rec.normal - gradient).len() < 1e-9);
+
+    let bbox = ellipsoid.bounding_box();
+    assert!(bbox.axis_interval(2).contains(-11.9));
+    assert!(bbox.axis_interval(0).max < 1.5);
+
+    // the shared object is untouched
+    let r = Ray {
+        origin: Vec3(0., 0., 5.),
+        direction: Vec3(0., 0., -1.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let rec = ball.hit(&r, &Interval::new(0.001, f64::INFINITY)).unwrap();
+    assert!((rec.t - 4.0).abs() < 1e-9);
+}
+
+#[cfg(test)]
+#[test]
+fn test_animated_instance() {
+    use crate::material::Lambertian;
+    use crate::sphere::Sphere;
+
+    let material = Lambertian::obj(Vec3(0.5, 0.5, 0.5));
+    let ball: Arc<dyn Hit> = Arc::from(Sphere::obj(Vec3::zero(), 1.0, material));
+    let motion = AnimatedTransform::new(&[
+        (0.0, Transform::identity()),
+        (1.0, Transform::translate(Vec3(10., 0., 0.))),
+    ]);
+    let moving = Instance::animated(ball, motion).translate(Vec3(0., 0., -10.));
+
+    let at = |x: f64, time: f64| {
+        let r = Ray {
+            origin: Vec3(x, 0., 0.),
+            direction: Vec3(0., 0., -1.),
+            time,
+            wavelength: None,
+        };
+        moving.hit(&r, &Interval::new(0.001, f64::INFINITY))
+    };
+    assert!(at(0.0, 0.0).is_some());
+    assert!(at(0.0, 1.0).is_none());
+    assert!(at(5.0, 0.5).is_some_and(|rec| (rec.t - 9.0).abs() < 1e-9));
+    assert!(at(10.0, 1.0).is_some());
+
+    let bbox = moving.bounding_box();
+    assert!(bbox.axis_interval(0).contains(-0.9) && bbox.axis_interval(0).contains(10.9));
+}
+
+#[cfg(test)]
+#[test]
+fn test_moving_sphere() {
+    use crate::material::Lambertian;
+    use crate::sphere::Sphere;
+
+    let material = Lambertian::obj(Vec3(0.5, 0.5, 0.5));
+    let moving =
+        Sphere::new(Vec3(0., 0., -10.), 1.0, material).with_motion(Vec3(10., 0., -10.), 0.0, 1.0);
+    let at = |ball: &Sphere, x: f64, time: f64| {
+        let r = Ray {
+            origin: Vec3(x, 0., 0.),
+            direction: Vec3(0., 0., -1.),
+            time,
+            wavelength: None,
+        };
+        ball.hit(&r, &Interval::new(0.001, f64::INFINITY))
+    };
+    assert!(at(&moving, 0.0, 0.0).is_some_and(|rec| (rec.t - 9.0).abs() < 1e-9));
+    assert!(at(&moving, 0.0, 1.0).is_none());
+    assert!(at(&moving, 5.0, 0.5).is_some_and(|rec| (rec.normal - Vec3(0., 0., 1.)).len() < 1e-9));
+    assert!(at(&moving, 10.0, 1.0).is_some());
+    // holds still at the ends
+    assert!(at(&moving, 10.0, 3.0).is_some());
+
+    let bbox = moving.bounding_box();
+    assert!(bbox.axis_interval(0).contains(-0.9) && bbox.axis_interval(0).contains(10.9));
+    assert!(!bbox.axis_interval(0).contains(11.1));
+
+    // times are absolute, like the shutter's
+    let material = Lambertian::obj(Vec3(0.5, 0.5, 0.5));
+    let later =
+        Sphere::new(Vec3(0., 0., -10.), 1.0, material).with_motion(Vec3(10., 0., -10.), 1.0, 2.0);
+    assert!(at(&later, 0.0, 0.5).is_some());
+    assert!(at(&later, 5.0, 1.5).is_some());
+    assert!(at(&later, 10.0, 2.0).is_some());
+}
@@ -1,9 +1,25 @@
+pub mod aabb;
+pub mod background;
+pub mod bvh;
 pub mod camera;
 pub mod color;
+pub mod environment;
+pub mod film;
+pub mod gltf;
 pub mod hittable;
+pub mod image;
+pub mod instance;
 pub mod interval;
 pub mod material;
 pub mod math;
+pub mod medium;
+pub mod mesh;
+pub mod perlin;
+pub mod quad;
 pub mod ray;
+pub mod scene;
+pub mod spectrum;
 pub mod sphere;
+pub mod texture;
+pub mod triangle;
 pub mod vec3;
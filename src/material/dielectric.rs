@@ -3,24 +3,114 @@ use super::Scatter;
 use crate::hittable::HitRecord;
 use crate::math::random;
 use crate::ray::Ray;
-use crate::vec3::{Vec3, dot, unit_vector};
-use std::rc::Rc;
+use crate::vec3::{Color, Vec3, dot, unit_vector};
+use std::sync::Arc;
+
+/// How the refractive index changes with wavelength, in micrometers.
+#[derive(Debug, Clone, Copy)]
+enum Dispersion {
+    None(f64),
+    /// `n = a + b / λ²`
+    Cauchy(f64, f64),
+    /// `n² = 1 + Σ b λ² / (λ² - c)`
+    Sellmeier([f64; 3], [f64; 3]),
+}
+
+/// Wavelength the index of non-spectral renders is taken at, the sodium D
+/// line glass catalogs quote.
+const SODIUM_D: f64 = 589.3;
 
 pub struct Dielectric {
-    refraction_index: f64,
+    dispersion: Dispersion,
+    /// Beer-Lambert absorption per unit length travelled inside
+    absorption: Color,
 }
 
 impl Dielectric {
+    /// Clear glass, or water, or diamond, by `refraction_index`.
     pub fn new(refraction_index: f64) -> Self {
-        Self { refraction_index }
+        Self {
+            dispersion: Dispersion::None(refraction_index),
+            absorption: Color::zero(),
+        }
+    }
+
+    pub fn obj(refraction_index: f64) -> Arc<dyn Material> {
+        Arc::new(Self::new(refraction_index))
+    }
+
+    /// Dispersive material following Cauchy's equation `n = a + b / λ²`,
+    /// with λ in micrometers. Only spectral renders split the colors.
+    pub fn cauchy(a: f64, b: f64) -> Self {
+        Self {
+            dispersion: Dispersion::Cauchy(a, b),
+            ..Self::new(a)
+        }
+    }
+
+    /// Dispersive material from the Sellmeier coefficients glass catalogs
+    /// list, with `c` in square micrometers.
+    pub fn sellmeier(b: [f64; 3], c: [f64; 3]) -> Self {
+        Self {
+            dispersion: Dispersion::Sellmeier(b, c),
+            ..Self::new(1.0)
+        }
+    }
+
+    /// Schott N-BK7, the common optical crown glass.
+    pub fn bk7() -> Self {
+        Self::sellmeier(
+            [1.03961212, 0.231792344, 1.01046945],
+            [0.00600069867, 0.0200179144, 103.560653],
+        )
+    }
+
+    /// Fused silica, quartz glass.
+    pub fn fused_silica() -> Self {
+        Self::sellmeier(
+            [0.6961663, 0.4079426, 0.8974794],
+            [0.0046791482, 0.0135120631, 97.9340025],
+        )
+    }
+
+    /// Diamond, which owes its fire to a high dispersion.
+    pub fn diamond() -> Self {
+        Self::sellmeier([0.3306, 4.3356, 0.0], [0.030625, 0.011236, 0.0])
     }
 
-    pub fn obj(refraction_index: f64) -> Rc<dyn Material> {
-        Rc::new(Self::new(refraction_index))
+    /// Index at `wavelength` in nanometers, or at the sodium D line.
+    pub fn refraction_index(&self, wavelength: Option<f64>) -> f64 {
+        let um = wavelength.unwrap_or(SODIUM_D) / 1000.0;
+        let um2 = um * um;
+        match self.dispersion {
+            Dispersion::None(n) => n,
+            Dispersion::Cauchy(a, b) => a + b / um2,
+            Dispersion::Sellmeier(b, c) => {
+                (1.0 + (0..3).map(|i| b[i] * um2 / (um2 - c[i])).sum::<f64>()).sqrt()
+            }
+        }
     }
 
-    fn reflectance(&self, cosine: f64) -> f64 {
-        let r0 = (1.0 - self.refraction_index) / (1.0 + self.refraction_index);
+    /// Tints the interior: light is dimmed by `exp(-absorption * distance)`
+    /// on its way through, so thick parts look darker than thin ones.
+    pub fn with_absorption(mut self, absorption: Color) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    /// Absorption that turns white light into `color` after `distance`
+    /// inside the material, which is easier to pick than coefficients.
+    pub fn with_color_at_distance(self, color: Color, distance: f64) -> Self {
+        let coefficient = |c: f64| -c.clamp(1e-6, 1.0).ln() / distance;
+        self.with_absorption(Vec3(
+            coefficient(color.0),
+            coefficient(color.1),
+            coefficient(color.2),
+        ))
+    }
+
+    fn reflectance(cosine: f64, refraction_index: f64) -> f64 {
+        let r0 = (1.0 - refraction_index) / (1.0 + refraction_index);
         let r0 = r0 * r0;
         r0 + (1.0 - r0) * f64::powi(1.0 - cosine, 5)
     }
@@ -28,27 +118,86 @@ impl Dielectric {
 
 impl Material for Dielectric {
     fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<Scatter> {
-        let attenuation = Vec3(1.0, 1.0, 1.0);
+        // leaving the interior, so the ray has just crossed it
+        let attenuation = if rec.front_face {
+            Color::one()
+        } else {
+            let distance = rec.t * r_in.direction.len();
+            Vec3(
+                (-self.absorption.0 * distance).exp(),
+                (-self.absorption.1 * distance).exp(),
+                (-self.absorption.2 * distance).exp(),
+            )
+        };
         let unit_direction = unit_vector(&r_in.direction);
+        let refraction_index = self.refraction_index(r_in.wavelength);
         let ri = if rec.front_face {
-            1.0 / self.refraction_index
+            1.0 / refraction_index
         } else {
-            self.refraction_index
+            refraction_index
         };
         let cos_theta = f64::min(dot(&-unit_direction, &rec.normal), 1.0);
         let sin_theta = f64::sqrt(1.0 - cos_theta * cos_theta);
-        let direction = if ri * sin_theta > 1.0 || self.reflectance(cos_theta) > random() {
-            unit_direction.reflect(&rec.normal)
-        } else {
-            unit_direction.refract(&rec.normal, ri)
-        };
+        let direction =
+            if ri * sin_theta > 1.0 || Self::reflectance(cos_theta, refraction_index) > random() {
+                unit_direction.reflect(&rec.normal)
+            } else {
+                unit_direction.refract(&rec.normal, ri)
+            };
 
         Some(Scatter {
             color_attenuation: attenuation,
             ray: Ray {
-                direction: direction,
+                direction,
                 origin: rec.p,
+                time: r_in.time,
+                wavelength: r_in.wavelength,
             },
         })
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_dielectric_absorption() {
+    use crate::hittable::HitRecord;
+
+    let glass = Dielectric::new(1.5).with_color_at_distance(Vec3(0.5, 0.25, 1.0), 2.0);
+    let material: Arc<dyn Material> = Arc::new(Dielectric::new(1.5));
+    let r = Ray {
+        origin: Vec3::zero(),
+        direction: Vec3(0., 0., -2.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let outward = Vec3(0., 0., -1.);
+    // 2 units of the ray direction cover 4 units of distance
+    let exit = HitRecord::with_normal(r.at(2.0), &r, outward, 2.0, material.clone());
+    let scatter = glass.scatter(&r, &exit).unwrap();
+    assert!((scatter.color_attenuation - Vec3(0.25, 0.0625, 1.0)).len() < 1e-9);
+
+    let entry = HitRecord::with_normal(r.at(2.0), &r, -outward, 2.0, material);
+    let scatter = glass.scatter(&r, &entry).unwrap();
+    assert_eq!(scatter.color_attenuation, Color::one());
+}
+
+#[cfg(test)]
+#[test]
+fn test_dielectric_dispersion() {
+    // catalog values at the helium d line, 587.6nm
+    for (glass, nd) in [
+        (Dielectric::bk7(), 1.5168),
+        (Dielectric::fused_silica(), 1.4585),
+        (Dielectric::diamond(), 2.4175),
+    ] {
+        assert!((glass.refraction_index(Some(587.6)) - nd).abs() < 1e-3);
+        let blue = glass.refraction_index(Some(450.0));
+        let red = glass.refraction_index(Some(650.0));
+        assert!(blue > red);
+        let n = glass.refraction_index(None);
+        assert!(red < n && n < blue);
+    }
+    let cauchy = Dielectric::cauchy(1.5, 0.01);
+    assert!((cauchy.refraction_index(Some(500.0)) - 1.54).abs() < 1e-12);
+    assert_eq!(Dielectric::new(1.33).refraction_index(Some(400.0)), 1.33);
+}
@@ -0,0 +1,32 @@
+use super::{Material, Scatter};
+use crate::hittable::HitRecord;
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::vec3::Color;
+use std::sync::Arc;
+
+/// Emits `emit` radiance from both sides and scatters nothing.
+pub struct DiffuseLight {
+    emit: Arc<dyn Texture>,
+}
+
+impl DiffuseLight {
+    /// `emit` is a texture or a flat `Color`.
+    pub fn new(emit: impl Into<Arc<dyn Texture>>) -> Self {
+        Self { emit: emit.into() }
+    }
+
+    pub fn obj(emit: impl Into<Arc<dyn Texture>>) -> Arc<dyn Material> {
+        Arc::new(Self::new(emit))
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _: &Ray, _: &HitRecord) -> Option<Scatter> {
+        None
+    }
+
+    fn emitted(&self, _: &Ray, hit: &HitRecord) -> Color {
+        self.emit.value(hit.u, hit.v, &hit.p)
+    }
+}
@@ -0,0 +1,108 @@
+use super::{Material, Scatter};
+use crate::hittable::HitRecord;
+use crate::math::random;
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::vec3::{Vec3, cross, dot, unit_vector};
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// Phase function of a medium that prefers scattering forward (`g > 0`),
+/// like haze and clouds, or backward (`g < 0`). `g = 0` is `Isotropic`.
+pub struct HenyeyGreenstein {
+    albedo: Arc<dyn Texture>,
+    g: f64,
+}
+
+impl HenyeyGreenstein {
+    /// `albedo` is a texture or a flat `Color`; `g` is the mean cosine of
+    /// the scattering angle, between -1 and 1.
+    pub fn new(albedo: impl Into<Arc<dyn Texture>>, g: f64) -> Self {
+        Self {
+            albedo: albedo.into(),
+            g: g.clamp(-0.999, 0.999),
+        }
+    }
+
+    pub fn obj(albedo: impl Into<Arc<dyn Texture>>, g: f64) -> Arc<dyn Material> {
+        Arc::new(Self::new(albedo, g))
+    }
+}
+
+/// Density of turning by an angle with cosine `cos_theta` away from the
+/// direction of travel.
+pub(crate) fn phase_pdf(g: f64, cos_theta: f64) -> f64 {
+    let denom = 1.0 + g * g - 2.0 * g * cos_theta;
+    (1.0 - g * g) / (4.0 * PI * denom * denom.sqrt())
+}
+
+/// Unit direction drawn from `phase_pdf` around the direction of travel.
+pub(crate) fn sample_phase(g: f64, travel: &Vec3) -> Vec3 {
+    let xi = random();
+    let cos_theta = if g.abs() < 1e-3 {
+        1.0 - 2.0 * xi
+    } else {
+        let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * xi);
+        ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+    };
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * PI * random();
+
+    let w = unit_vector(travel);
+    let helper = if w.0.abs() > 0.9 {
+        Vec3(0., 1., 0.)
+    } else {
+        Vec3(1., 0., 0.)
+    };
+    let u = unit_vector(&cross(&helper, &w));
+    let v = cross(&w, &u);
+    sin_theta * phi.cos() * u + sin_theta * phi.sin() * v + cos_theta * w
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, ray_in: &Ray, hit: &HitRecord) -> Option<Scatter> {
+        Some(Scatter {
+            color_attenuation: self.albedo.value(hit.u, hit.v, &hit.p),
+            ray: Ray {
+                origin: hit.p,
+                direction: sample_phase(self.g, &ray_in.direction),
+                time: ray_in.time,
+                wavelength: ray_in.wavelength,
+            },
+        })
+    }
+
+    fn scattering_pdf(&self, ray_in: &Ray, _: &HitRecord, direction: &Vec3) -> f64 {
+        let cos_theta = dot(&unit_vector(&ray_in.direction), &unit_vector(direction));
+        phase_pdf(self.g, cos_theta)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_henyey_greenstein() {
+    for g in [-0.6, 0.0, 0.3, 0.9] {
+        // the density integrates to one over the sphere
+        let steps = 20_000;
+        let integral: f64 = (0..steps)
+            .map(|i| {
+                let cos = -1.0 + 2.0 * (i as f64 + 0.5) / steps as f64;
+                2.0 * PI * phase_pdf(g, cos) * 2.0 / steps as f64
+            })
+            .sum();
+        assert!((integral - 1.0).abs() < 1e-3, "g = {g}: {integral}");
+
+        // and samples have a mean cosine of g
+        let travel = Vec3(0., 0., -3.);
+        let n = 50_000;
+        let mean: f64 = (0..n)
+            .map(|_| {
+                let d = sample_phase(g, &travel);
+                assert!((d.len() - 1.0).abs() < 1e-9);
+                -d.2
+            })
+            .sum::<f64>()
+            / n as f64;
+        assert!((mean - g).abs() < 0.02, "g = {g}: {mean}");
+    }
+}
@@ -0,0 +1,44 @@
+use super::{Material, Scatter};
+use crate::hittable::HitRecord;
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::vec3::Vec3;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// Phase function of a participating medium that scatters equally in every
+/// direction. Used as the material of volume hits.
+pub struct Isotropic {
+    albedo: Arc<dyn Texture>,
+}
+
+impl Isotropic {
+    /// `albedo` is a texture or a flat `Color`.
+    pub fn new(albedo: impl Into<Arc<dyn Texture>>) -> Self {
+        Self {
+            albedo: albedo.into(),
+        }
+    }
+
+    pub fn obj(albedo: impl Into<Arc<dyn Texture>>) -> Arc<dyn Material> {
+        Arc::new(Self::new(albedo))
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, ray_in: &Ray, hit: &HitRecord) -> Option<Scatter> {
+        Some(Scatter {
+            color_attenuation: self.albedo.value(hit.u, hit.v, &hit.p),
+            ray: Ray {
+                origin: hit.p,
+                direction: Vec3::unit_random(),
+                time: ray_in.time,
+                wavelength: ray_in.wavelength,
+            },
+        })
+    }
+
+    fn scattering_pdf(&self, _: &Ray, _: &HitRecord, _: &Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+}
@@ -1,26 +1,31 @@
 pub use super::{Material, Scatter};
 use crate::hittable::HitRecord;
 use crate::ray::Ray;
-use crate::vec3::{Color, Vec3};
-use std::rc::Rc;
+use crate::texture::Texture;
+use crate::vec3::{Vec3, dot, unit_vector};
+use std::f64::consts::PI;
+use std::sync::Arc;
 
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
-    pub fn new(albedo: Color) -> Self {
-        Self { albedo }
+    /// `albedo` is a texture or a flat `Color`.
+    pub fn new(albedo: impl Into<Arc<dyn Texture>>) -> Self {
+        Self {
+            albedo: albedo.into(),
+        }
     }
 
-    pub fn obj(albedo: Color) -> Rc<dyn Material> {
+    pub fn obj(albedo: impl Into<Arc<dyn Texture>>) -> Arc<dyn Material> {
         let lambertian = Self::new(albedo);
-        Rc::new(lambertian)
+        Arc::new(lambertian)
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _: &Ray, hit: &HitRecord) -> Option<Scatter> {
+    fn scatter(&self, ray_in: &Ray, hit: &HitRecord) -> Option<Scatter> {
         let mut direction = hit.normal + Vec3::unit_random();
         if direction.near_zero() {
             direction = hit.normal;
@@ -28,10 +33,17 @@ impl Material for Lambertian {
         let ray = Ray {
             direction,
             origin: hit.p,
+            time: ray_in.time,
+            wavelength: ray_in.wavelength,
         };
         Some(Scatter {
-            color_attenuation: self.albedo,
+            color_attenuation: self.albedo.value(hit.u, hit.v, &hit.p),
             ray,
         })
     }
+
+    fn scattering_pdf(&self, _: &Ray, hit: &HitRecord, direction: &Vec3) -> f64 {
+        let cosine = dot(&hit.normal, &unit_vector(direction));
+        cosine.max(0.0) / PI
+    }
 }
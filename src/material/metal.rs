@@ -1,22 +1,108 @@
 use super::Material;
 use super::Scatter;
 use crate::ray::Ray;
+use crate::texture::Texture;
 use crate::vec3::{Color, Vec3, dot, unit_vector};
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub struct Metal {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
     roughness: f64,
+    /// complex refractive index `η + ik` per RGB channel, for conductor
+    /// Fresnel reflectance
+    complex_ior: Option<(Color, Color)>,
 }
 
 impl Metal {
-    pub fn new(albedo: Color, roughness: f64) -> Self {
-        Self { albedo, roughness }
+    /// `albedo` is a texture or a flat `Color`.
+    pub fn new(albedo: impl Into<Arc<dyn Texture>>, roughness: f64) -> Self {
+        Self {
+            albedo: albedo.into(),
+            roughness,
+            complex_ior: None,
+        }
+    }
+
+    pub fn obj(albedo: impl Into<Arc<dyn Texture>>, roughness: f64) -> Arc<dyn Material> {
+        Arc::new(Self::new(albedo, roughness))
+    }
+
+    /// A conductor whose reflectance follows the Fresnel equations for the
+    /// complex index `eta + ik`, turning whiter toward grazing angles.
+    pub fn conductor(eta: Color, k: Color, roughness: f64) -> Self {
+        Self::new(Color::one(), roughness).with_complex_ior(eta, k)
+    }
+
+    /// Uses conductor Fresnel reflectance, still tinted by the albedo.
+    pub fn with_complex_ior(mut self, eta: Color, k: Color) -> Self {
+        self.complex_ior = Some((eta, k));
+        self
+    }
+
+    pub fn gold(roughness: f64) -> Self {
+        Self::conductor(
+            Vec3(0.143, 0.374, 1.442),
+            Vec3(3.983, 2.385, 1.603),
+            roughness,
+        )
+    }
+
+    pub fn silver(roughness: f64) -> Self {
+        Self::conductor(
+            Vec3(0.155, 0.117, 0.138),
+            Vec3(4.828, 3.122, 2.147),
+            roughness,
+        )
+    }
+
+    pub fn copper(roughness: f64) -> Self {
+        Self::conductor(
+            Vec3(0.200, 0.924, 1.102),
+            Vec3(3.912, 2.452, 2.142),
+            roughness,
+        )
     }
 
-    pub fn obj(albedo: Color, roughness: f64) -> Rc<dyn Material> {
-        Rc::new(Self::new(albedo, roughness))
+    pub fn aluminum(roughness: f64) -> Self {
+        Self::conductor(
+            Vec3(1.657, 0.880, 0.521),
+            Vec3(9.224, 6.270, 4.837),
+            roughness,
+        )
     }
+
+    pub fn chromium(roughness: f64) -> Self {
+        Self::conductor(
+            Vec3(4.360, 2.910, 1.650),
+            Vec3(5.200, 4.230, 3.750),
+            roughness,
+        )
+    }
+
+    pub fn titanium(roughness: f64) -> Self {
+        Self::conductor(
+            Vec3(2.740, 2.480, 2.160),
+            Vec3(3.810, 3.420, 3.040),
+            roughness,
+        )
+    }
+}
+
+/// Unpolarized Fresnel reflectance of a conductor with index `eta + ik`, for
+/// light arriving at `cosine` to the normal.
+fn fresnel_conductor(cosine: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cosine * cosine;
+    let sin2 = 1.0 - cos2;
+    let t0 = eta * eta - k * k - sin2;
+    let a2_plus_b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+    let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t1 = a2_plus_b2 + cos2;
+    let t2 = 2.0 * cosine * a;
+    let rs = (t1 - t2) / (t1 + t2);
+    let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+    0.5 * (rs + rp)
 }
 
 impl Material for Metal {
@@ -26,10 +112,22 @@ impl Material for Metal {
         let ray = Ray {
             direction: reflected,
             origin: hit.p,
+            time: ray_in.time,
+            wavelength: ray_in.wavelength,
         };
         if dot(&ray.direction, &hit.normal) > 0.0 {
+            let mut color_attenuation = self.albedo.value(hit.u, hit.v, &hit.p);
+            if let Some((eta, k)) = self.complex_ior {
+                let cosine = (-dot(&unit_vector(&ray_in.direction), &hit.normal)).clamp(0.0, 1.0);
+                color_attenuation = color_attenuation
+                    * Vec3(
+                        fresnel_conductor(cosine, eta.0, k.0),
+                        fresnel_conductor(cosine, eta.1, k.1),
+                        fresnel_conductor(cosine, eta.2, k.2),
+                    );
+            }
             Some(Scatter {
-                color_attenuation: self.albedo,
+                color_attenuation,
                 ray,
             })
         } else {
@@ -37,3 +135,49 @@ impl Material for Metal {
         }
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_metal_conductor() {
+    use crate::hittable::HitRecord;
+
+    // k = 0 reduces to the dielectric Fresnel equations
+    assert!((fresnel_conductor(1.0, 1.5, 0.0) - 0.04).abs() < 1e-12);
+    assert!((fresnel_conductor(0.0, 1.5, 0.0) - 1.0).abs() < 1e-12);
+
+    let gold = Metal::gold(0.0);
+    let material: Arc<dyn Material> = Arc::new(Metal::gold(0.0));
+    let reflect_at = |cosine: f64| {
+        let sine = (1.0 - cosine * cosine).sqrt();
+        let r = Ray {
+            origin: Vec3(-sine, cosine, 0.),
+            direction: Vec3(sine, -cosine, 0.),
+            time: 0.0,
+            wavelength: None,
+        };
+        let hit = HitRecord::with_normal(r.at(1.0), &r, Vec3(0., 1., 0.), 1.0, material.clone());
+        gold.scatter(&r, &hit).unwrap().color_attenuation
+    };
+    // yellow head on, close to the measured reflectance of gold
+    let head_on = reflect_at(1.0);
+    assert!((head_on - Vec3(0.967, 0.800, 0.325)).len() < 0.01);
+    // whiter toward grazing angles, with blue gaining the most
+    let grazing = reflect_at(0.05);
+    assert!(grazing.0 > head_on.0 && grazing.1 > head_on.1 && grazing.2 > head_on.2);
+    assert!(grazing.2 - head_on.2 > grazing.0 - head_on.0);
+    assert!(grazing.2 > 0.7);
+
+    // flat albedo metals are unchanged
+    let plain = Metal::new(Vec3(0.8, 0.6, 0.2), 0.0);
+    let r = Ray {
+        origin: Vec3(0., 1., 0.),
+        direction: Vec3(0., -1., 0.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let hit = HitRecord::with_normal(r.at(1.0), &r, Vec3(0., 1., 0.), 1.0, material);
+    assert_eq!(
+        plain.scatter(&r, &hit).unwrap().color_attenuation,
+        Vec3(0.8, 0.6, 0.2)
+    );
+}
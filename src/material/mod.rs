@@ -1,20 +1,40 @@
 mod dielectric;
+mod diffuse_light;
+pub(crate) mod henyey_greenstein;
+mod isotropic;
 mod lambertian;
 mod metal;
 
 pub use dielectric::Dielectric;
+pub use diffuse_light::DiffuseLight;
+pub use henyey_greenstein::HenyeyGreenstein;
+pub use isotropic::Isotropic;
 pub use lambertian::Lambertian;
 pub use metal::Metal;
 
 use crate::hittable::HitRecord;
 use crate::ray::Ray;
-use crate::vec3::Color;
+use crate::vec3::{Color, Vec3};
 
 pub struct Scatter {
     pub color_attenuation: Color,
     pub ray: Ray,
 }
 
-pub trait Material {
+pub trait Material: Send + Sync {
     fn scatter(&self, ray_in: &Ray, hit: &HitRecord) -> Option<Scatter>;
+
+    /// Solid angle density with which `scatter` would have sent the ray out
+    /// along `direction`. Materials that report a density promise that
+    /// `color_attenuation * scattering_pdf` is their BRDF times cosine for
+    /// any direction, which lets the camera aim bounces at bright lights.
+    /// Specular materials keep the default of zero.
+    fn scattering_pdf(&self, _ray_in: &Ray, _hit: &HitRecord, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    /// Radiance given off at the hit point toward the incoming ray.
+    fn emitted(&self, _ray_in: &Ray, _hit: &HitRecord) -> Color {
+        Color::zero()
+    }
 }
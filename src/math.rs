@@ -1,4 +1,7 @@
+use crate::aabb::Aabb;
+use crate::vec3::{Point, Vec3, unit_vector};
 use rand::prelude::*;
+use std::ops::Mul;
 
 pub fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * std::f64::consts::PI / 180.0
@@ -11,3 +14,502 @@ pub fn random() -> f64 {
 pub fn random_f64(min: f64, max: f64) -> f64 {
     random() * (max - min) + min
 }
+
+/// Affine transform stored as a row-major 4x4 matrix together with its
+/// inverse.
+///
+/// `a * b` applies `b` first, so
+/// `Transform::translate(t) * Transform::rotate(&axis, 30.)` spins an object
+/// about its own origin and then moves it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    m: [[f64; 4]; 4],
+    inv: [[f64; 4]; 4],
+}
+
+const IDENTITY: [[f64; 4]; 4] = [
+    [1., 0., 0., 0.],
+    [0., 1., 0., 0.],
+    [0., 0., 1., 0.],
+    [0., 0., 0., 1.],
+];
+
+fn mat_mul(a: &[[f64; 4]; 4], b: &[[f64; 4]; 4]) -> [[f64; 4]; 4] {
+    let mut m = [[0.0; 4]; 4];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, x) in row.iter_mut().enumerate() {
+            *x = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+/// Gauss-Jordan elimination with partial pivoting; `None` when singular.
+fn mat_inverse(m: &[[f64; 4]; 4]) -> Option<[[f64; 4]; 4]> {
+    let mut a = *m;
+    let mut inv = IDENTITY;
+    for col in 0..4 {
+        let pivot = (col..4).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        inv.swap(col, pivot);
+        let scale = 1.0 / a[col][col];
+        for k in 0..4 {
+            a[col][k] *= scale;
+            inv[col][k] *= scale;
+        }
+        for row in 0..4 {
+            if row != col {
+                let f = a[row][col];
+                for k in 0..4 {
+                    a[row][k] -= f * a[col][k];
+                    inv[row][k] -= f * inv[col][k];
+                }
+            }
+        }
+    }
+    Some(inv)
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            m: IDENTITY,
+            inv: IDENTITY,
+        }
+    }
+
+    /// From a row-major matrix whose last row is `0 0 0 1`. Returns `None`
+    /// when the matrix cannot be inverted.
+    pub fn from_matrix(m: [[f64; 4]; 4]) -> Option<Self> {
+        Some(Self {
+            m,
+            inv: mat_inverse(&m)?,
+        })
+    }
+
+    pub fn translate(offset: Vec3) -> Self {
+        let mut t = Self::identity();
+        for (i, x) in [offset.0, offset.1, offset.2].into_iter().enumerate() {
+            t.m[i][3] = x;
+            t.inv[i][3] = -x;
+        }
+        t
+    }
+
+    /// Scales each axis by the matching component, which must not be zero.
+    pub fn scale(factors: Vec3) -> Self {
+        let mut t = Self::identity();
+        for (i, x) in [factors.0, factors.1, factors.2].into_iter().enumerate() {
+            t.m[i][i] = x;
+            t.inv[i][i] = 1.0 / x;
+        }
+        t
+    }
+
+    /// Counter clockwise rotation by `degrees` about `axis`, looking down the
+    /// axis towards the origin.
+    pub fn rotate(axis: &Vec3, degrees: f64) -> Self {
+        let a = unit_vector(axis);
+        let (sin, cos) = degrees_to_radians(degrees).sin_cos();
+        let mut m = IDENTITY;
+        m[0][0] = a.0 * a.0 + (1.0 - a.0 * a.0) * cos;
+        m[0][1] = a.0 * a.1 * (1.0 - cos) - a.2 * sin;
+        m[0][2] = a.0 * a.2 * (1.0 - cos) + a.1 * sin;
+        m[1][0] = a.0 * a.1 * (1.0 - cos) + a.2 * sin;
+        m[1][1] = a.1 * a.1 + (1.0 - a.1 * a.1) * cos;
+        m[1][2] = a.1 * a.2 * (1.0 - cos) - a.0 * sin;
+        m[2][0] = a.0 * a.2 * (1.0 - cos) - a.1 * sin;
+        m[2][1] = a.1 * a.2 * (1.0 - cos) + a.0 * sin;
+        m[2][2] = a.2 * a.2 + (1.0 - a.2 * a.2) * cos;
+        // rotations are orthogonal, so the inverse is the transpose
+        let mut inv = IDENTITY;
+        for (i, row) in inv.iter_mut().enumerate().take(3) {
+            for (j, x) in row.iter_mut().enumerate().take(3) {
+                *x = m[j][i];
+            }
+        }
+        Self { m, inv }
+    }
+
+    /// Rotation given as a unit quaternion `x i + y j + z k + w`.
+    pub fn rotate_quaternion(x: f64, y: f64, z: f64, w: f64) -> Self {
+        let m = [
+            [
+                1. - 2. * (y * y + z * z),
+                2. * (x * y - z * w),
+                2. * (x * z + y * w),
+                0.,
+            ],
+            [
+                2. * (x * y + z * w),
+                1. - 2. * (x * x + z * z),
+                2. * (y * z - x * w),
+                0.,
+            ],
+            [
+                2. * (x * z - y * w),
+                2. * (y * z + x * w),
+                1. - 2. * (x * x + y * y),
+                0.,
+            ],
+            [0., 0., 0., 1.],
+        ];
+        let mut inv = m;
+        for (i, row) in inv.iter_mut().enumerate().take(3) {
+            for (j, x) in row.iter_mut().enumerate().take(3) {
+                *x = m[j][i];
+            }
+        }
+        Self { m, inv }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            m: self.inv,
+            inv: self.m,
+        }
+    }
+
+    /// the row-major matrix
+    pub fn matrix(&self) -> &[[f64; 4]; 4] {
+        &self.m
+    }
+
+    pub fn point(&self, p: &Point) -> Point {
+        self.vector(p) + Vec3(self.m[0][3], self.m[1][3], self.m[2][3])
+    }
+
+    pub fn vector(&self, v: &Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3(
+            m[0][0] * v.0 + m[0][1] * v.1 + m[0][2] * v.2,
+            m[1][0] * v.0 + m[1][1] * v.1 + m[1][2] * v.2,
+            m[2][0] * v.0 + m[2][1] * v.1 + m[2][2] * v.2,
+        )
+    }
+
+    /// Transforms a surface normal with the inverse transpose, so it stays
+    /// perpendicular to transformed tangents. The result is not normalized.
+    pub fn normal(&self, n: &Vec3) -> Vec3 {
+        let inv = &self.inv;
+        Vec3(
+            inv[0][0] * n.0 + inv[1][0] * n.1 + inv[2][0] * n.2,
+            inv[0][1] * n.0 + inv[1][1] * n.1 + inv[2][1] * n.2,
+            inv[0][2] * n.0 + inv[1][2] * n.1 + inv[2][2] * n.2,
+        )
+    }
+
+    /// Whether the transform mirrors space, turning winding orders around.
+    pub fn swaps_handedness(&self) -> bool {
+        let m = &self.m;
+        let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+        det < 0.0
+    }
+
+    /// Box around the transformed corners of `bbox`.
+    pub fn bounding_box(&self, bbox: &Aabb) -> Aabb {
+        if bbox.is_empty() {
+            return *bbox;
+        }
+        let (min, max) = (bbox.min(), bbox.max());
+        (0..8).fold(Aabb::empty(), |out, corner| {
+            let p = Vec3(
+                if corner & 1 == 0 { min.0 } else { max.0 },
+                if corner & 2 == 0 { min.1 } else { max.1 },
+                if corner & 4 == 0 { min.2 } else { max.2 },
+            );
+            out.enclose(self.point(&p))
+        })
+    }
+}
+
+impl Mul for Transform {
+    type Output = Transform;
+    fn mul(self, rhs: Transform) -> Transform {
+        Transform {
+            m: mat_mul(&self.m, &rhs.m),
+            inv: mat_mul(&rhs.inv, &self.inv),
+        }
+    }
+}
+
+/// Translation, rotation and remaining stretch of one keyframe, which
+/// interpolate without the shearing a blend of raw matrices shows.
+#[derive(Debug, Clone, Copy)]
+struct Keyframe {
+    time: f64,
+    translation: Vec3,
+    /// unit quaternion `[x, y, z, w]`
+    rotation: [f64; 4],
+    /// scale and shear, applied before the rotation
+    stretch: [[f64; 3]; 3],
+}
+
+impl Keyframe {
+    /// Splits `m = T R S` with a polar decomposition of the linear part.
+    fn new(time: f64, t: &Transform) -> Self {
+        let m = &t.m;
+        let linear: [[f64; 3]; 3] = std::array::from_fn(|i| std::array::from_fn(|j| m[i][j]));
+        let mut r = linear;
+        for _ in 0..100 {
+            // average with the inverse transpose until orthogonal
+            let it = inverse_transpose3(&r);
+            let next: [[f64; 3]; 3] =
+                std::array::from_fn(|i| std::array::from_fn(|j| 0.5 * (r[i][j] + it[i][j])));
+            let change: f64 = (0..9)
+                .map(|k| (next[k / 3][k % 3] - r[k / 3][k % 3]).abs())
+                .sum();
+            r = next;
+            if change < 1e-12 {
+                break;
+            }
+        }
+        // a mirror is not a rotation, so it moves into the stretch
+        if det3(&r) < 0.0 {
+            r = r.map(|row| row.map(|x| -x));
+        }
+        let stretch = std::array::from_fn(|i| {
+            std::array::from_fn(|j| (0..3).map(|k| r[k][i] * linear[k][j]).sum())
+        });
+        Self {
+            time,
+            translation: Vec3(m[0][3], m[1][3], m[2][3]),
+            rotation: quaternion(&r),
+            stretch,
+        }
+    }
+
+    fn lerp(&self, other: &Self, s: f64) -> Transform {
+        let translation = (1.0 - s) * self.translation + s * other.translation;
+        let [x, y, z, w] = slerp(&self.rotation, &other.rotation, s);
+        let mut stretch = IDENTITY;
+        for (i, row) in stretch.iter_mut().enumerate().take(3) {
+            for (j, v) in row.iter_mut().enumerate().take(3) {
+                *v = (1.0 - s) * self.stretch[i][j] + s * other.stretch[i][j];
+            }
+        }
+        let stretch = Transform::from_matrix(stretch).unwrap_or_default();
+        Transform::translate(translation) * Transform::rotate_quaternion(x, y, z, w) * stretch
+    }
+}
+
+fn det3(m: &[[f64; 3]; 3]) -> f64 {
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+/// Cofactors over the determinant.
+fn inverse_transpose3(m: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let det = det3(m);
+    std::array::from_fn(|r| {
+        std::array::from_fn(|c| {
+            let (r1, r2) = ((r + 1) % 3, (r + 2) % 3);
+            let (c1, c2) = ((c + 1) % 3, (c + 2) % 3);
+            (m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1]) / det
+        })
+    })
+}
+
+/// Quaternion of a rotation matrix.
+fn quaternion(m: &[[f64; 3]; 3]) -> [f64; 4] {
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    let q = if trace > 0.0 {
+        let s = 0.5 / (trace + 1.0).sqrt();
+        [
+            (m[2][1] - m[1][2]) * s,
+            (m[0][2] - m[2][0]) * s,
+            (m[1][0] - m[0][1]) * s,
+            0.25 / s,
+        ]
+    } else {
+        // build from the largest diagonal entry for stability
+        let i = (0..3).max_by(|&a, &b| m[a][a].total_cmp(&m[b][b])).unwrap();
+        let (j, k) = ((i + 1) % 3, (i + 2) % 3);
+        let s = 2.0 * (1.0 + m[i][i] - m[j][j] - m[k][k]).sqrt();
+        let mut q = [0.0; 4];
+        q[i] = 0.25 * s;
+        q[j] = (m[j][i] + m[i][j]) / s;
+        q[k] = (m[k][i] + m[i][k]) / s;
+        q[3] = (m[k][j] - m[j][k]) / s;
+        q
+    };
+    let norm = q.iter().map(|x| x * x).sum::<f64>().sqrt();
+    q.map(|x| x / norm)
+}
+
+/// Spherical interpolation along the shorter arc.
+fn slerp(a: &[f64; 4], b: &[f64; 4], s: f64) -> [f64; 4] {
+    let mut cos = (0..4).map(|i| a[i] * b[i]).sum::<f64>();
+    let b = if cos < 0.0 {
+        cos = -cos;
+        b.map(|x| -x)
+    } else {
+        *b
+    };
+    let (wa, wb) = if cos > 0.9995 {
+        (1.0 - s, s)
+    } else {
+        let theta = cos.acos();
+        let sin = theta.sin();
+        (((1.0 - s) * theta).sin() / sin, (s * theta).sin() / sin)
+    };
+    let q: [f64; 4] = std::array::from_fn(|i| wa * a[i] + wb * b[i]);
+    let norm = q.iter().map(|x| x * x).sum::<f64>().sqrt();
+    q.map(|x| x / norm)
+}
+
+/// Transform that changes over time, given at keyframes.
+///
+/// Between keys the translation and stretch are blended linearly and the
+/// rotation spherically, so a spinning object keeps its shape. Before the
+/// first key and after the last the nearest key holds.
+#[derive(Debug, Clone)]
+pub struct AnimatedTransform {
+    keys: Vec<Keyframe>,
+}
+
+impl AnimatedTransform {
+    /// Keys are `(time, transform)` pairs in any order; there must be at
+    /// least one.
+    pub fn new(keys: &[(f64, Transform)]) -> Self {
+        assert!(!keys.is_empty(), "an animated transform needs a keyframe");
+        let mut keys: Vec<Keyframe> = keys.iter().map(|(t, m)| Keyframe::new(*t, m)).collect();
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Self { keys }
+    }
+
+    pub fn at(&self, time: f64) -> Transform {
+        let next = self.keys.partition_point(|k| k.time <= time);
+        match (
+            next.checked_sub(1).map(|i| &self.keys[i]),
+            self.keys.get(next),
+        ) {
+            (Some(a), Some(b)) => a.lerp(b, (time - a.time) / (b.time - a.time)),
+            (Some(key), None) | (None, Some(key)) => key.lerp(key, 0.0),
+            (None, None) => unreachable!("keys are never empty"),
+        }
+    }
+
+    /// Box around `bbox` over the whole animation.
+    pub fn bounding_box(&self, bbox: &Aabb) -> Aabb {
+        if bbox.is_empty() || self.keys.len() == 1 {
+            return self.keys[0].lerp(&self.keys[0], 0.0).bounding_box(bbox);
+        }
+        const STEPS: usize = 32;
+        let (min, max) = (bbox.min(), bbox.max());
+        let corners: [Point; 8] = std::array::from_fn(|c| {
+            Vec3(
+                if c & 1 == 0 { min.0 } else { max.0 },
+                if c & 2 == 0 { min.1 } else { max.1 },
+                if c & 4 == 0 { min.2 } else { max.2 },
+            )
+        });
+        let mut out = Aabb::empty();
+        let mut step = 0.0f64;
+        let mut previous: Option<[Point; 8]> = None;
+        for pair in self.keys.windows(2) {
+            for i in 0..=STEPS {
+                let t = pair[0].lerp(&pair[1], i as f64 / STEPS as f64);
+                let moved = corners.map(|c| t.point(&c));
+                if let Some(previous) = previous {
+                    for (a, b) in previous.iter().zip(&moved) {
+                        step = step.max((*b - *a).len());
+                    }
+                }
+                out = moved.iter().fold(out, |out, p| out.enclose(*p));
+                previous = Some(moved);
+            }
+        }
+        // corners travel along arcs between the samples, never further than
+        // half a step from one of them
+        let pad = Vec3(step, step, step) * 0.55;
+        Aabb::from_points(out.min() - pad, out.max() + pad)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_transform() {
+    let t = Transform::translate(Vec3(1., 2., 3.))
+        * Transform::rotate(&Vec3(0., 0., 1.), 90.)
+        * Transform::scale(Vec3(2., 2., 2.));
+    let p = t.point(&Vec3(1., 0., 0.));
+    assert!((p - Vec3(1., 4., 3.)).len() < 1e-12);
+    assert!((t.inverse().point(&p) - Vec3(1., 0., 0.)).len() < 1e-12);
+    // vectors ignore the translation
+    assert!((t.vector(&Vec3(0., 1., 0.)) - Vec3(-2., 0., 0.)).len() < 1e-12);
+
+    let general = Transform::from_matrix(*t.matrix()).unwrap();
+    for (a, b) in general.inverse().matrix().iter().zip(t.inverse().matrix()) {
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-12);
+        }
+    }
+    assert!(Transform::from_matrix([[0.0; 4]; 4]).is_none());
+
+    // normals stay perpendicular to the surface under non-uniform scale
+    let squash = Transform::scale(Vec3(1., 4., 1.));
+    let tangent = squash.vector(&Vec3(1., -1., 0.));
+    let normal = squash.normal(&Vec3(1., 1., 0.));
+    assert!(crate::vec3::dot(&tangent, &normal).abs() < 1e-12);
+    assert!(Transform::scale(Vec3(-1., 1., 1.)).swaps_handedness());
+    assert!(!t.swaps_handedness());
+    let q = Transform::rotate_quaternion(0., (0.5f64).sqrt(), 0., (0.5f64).sqrt());
+    let r = Transform::rotate(&Vec3(0., 1., 0.), 90.);
+    assert!((q.point(&Vec3(0., 0., -1.)) - r.point(&Vec3(0., 0., -1.))).len() < 1e-12);
+}
+
+#[cfg(test)]
+#[test]
+fn test_animated_transform() {
+    let start = Transform::translate(Vec3(0., 0., 0.));
+    let end = Transform::translate(Vec3(4., 0., 0.))
+        * Transform::rotate(&Vec3(0., 1., 0.), 90.)
+        * Transform::scale(Vec3(1., 3., 1.));
+    let motion = AnimatedTransform::new(&[(1.0, end), (0.0, start)]);
+
+    // the keys themselves come back
+    let p = Vec3(1., 1., 0.);
+    assert!((motion.at(1.0).point(&p) - end.point(&p)).len() < 1e-9);
+    assert!((motion.at(0.0).point(&p) - p).len() < 1e-9);
+    assert!((motion.at(-5.0).point(&p) - p).len() < 1e-9);
+    assert!((motion.at(7.0).point(&p) - end.point(&p)).len() < 1e-9);
+
+    // halfway the rotation is 45 degrees, not a squashed blend
+    let half = motion.at(0.5);
+    let x = half.vector(&Vec3(1., 0., 0.));
+    assert!((x.len() - 1.0).abs() < 1e-9);
+    assert!((x - Vec3(0.5f64.sqrt(), 0., -(0.5f64.sqrt()))).len() < 1e-9);
+    assert!((half.vector(&Vec3(0., 1., 0.)) - Vec3(0., 2., 0.)).len() < 1e-9);
+    assert!((half.point(&Vec3::zero()) - Vec3(2., 0., 0.)).len() < 1e-9);
+
+    let bbox = motion.bounding_box(&Aabb::from_points(Vec3(-1., -1., -1.), Vec3(1., 1., 1.)));
+    for i in 0..=100 {
+        let t = motion.at(i as f64 / 100.0);
+        for p in [Vec3(1., 1., 1.), Vec3(-1., -1., 1.), Vec3(1., -1., -1.)] {
+            let q = t.point(&p);
+            assert!((0..3).all(|a| bbox.axis_interval(a).contains([q.0, q.1, q.2][a])));
+        }
+    }
+
+    // mirrors survive the decomposition
+    let mirror = Transform::scale(Vec3(-1., 2., 1.)) * Transform::rotate(&Vec3(1., 1., 0.), 30.);
+    let key = AnimatedTransform::new(&[(0.0, mirror)]).at(0.0);
+    for (a, b) in key.matrix().iter().zip(mirror.matrix()) {
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-9);
+        }
+    }
+}
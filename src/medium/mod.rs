@@ -0,0 +1,142 @@
+//! Participating media: fog, smoke and clouds.
+
+mod grid;
+
+pub use grid::{DensityGrid, GridMedium};
+
+use crate::aabb::Aabb;
+use crate::hittable::{Hit, HitRecord};
+use crate::interval::{Interval, UNIVERSE_INTERVAL};
+use crate::material::{Isotropic, Material};
+use crate::math::random;
+use crate::ray::Ray;
+use crate::texture::Texture;
+use crate::vec3::Vec3;
+use std::sync::Arc;
+
+/// Fog of uniform `density` filling a closed, convex `boundary`.
+///
+/// A ray passing through scatters at an exponentially distributed distance,
+/// so thin volumes let most light through and dense ones look almost solid.
+pub struct ConstantMedium {
+    boundary: Box<dyn Hit>,
+    /// `-1 / density`, the mean free path with the sign folded in
+    neg_inv_density: f64,
+    phase: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    /// `albedo` is a texture or a flat `Color` for the `Isotropic` phase
+    /// function.
+    pub fn new(boundary: Box<dyn Hit>, density: f64, albedo: impl Into<Arc<dyn Texture>>) -> Self {
+        Self {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            phase: Isotropic::obj(albedo),
+        }
+    }
+
+    pub fn obj(
+        boundary: Box<dyn Hit>,
+        density: f64,
+        albedo: impl Into<Arc<dyn Texture>>,
+    ) -> Box<dyn Hit> {
+        Box::new(Self::new(boundary, density, albedo))
+    }
+
+    /// Replaces the isotropic phase function, e.g. with a
+    /// `HenyeyGreenstein` for haze that glows around lights.
+    pub fn with_phase(mut self, phase: Arc<dyn Material>) -> Self {
+        self.phase = phase;
+        self
+    }
+}
+
+impl Hit for ConstantMedium {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        // where the ray enters and leaves the boundary, even behind it
+        let enter = self.boundary.hit(r, &UNIVERSE_INTERVAL)?;
+        let leave = self
+            .boundary
+            .hit(r, &Interval::new(enter.t + 0.0001, f64::INFINITY))?;
+        let t_min = enter.t.max(ray_t.min).max(0.0);
+        let t_max = leave.t.min(ray_t.max);
+        if t_min >= t_max {
+            return None;
+        }
+
+        let ray_length = r.direction.len();
+        let distance_inside = (t_max - t_min) * ray_length;
+        let hit_distance = self.neg_inv_density * random().ln();
+        if hit_distance > distance_inside {
+            return None;
+        }
+        let t = t_min + hit_distance / ray_length;
+        Some(volume_hit(r, t, Arc::clone(&self.phase)))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+}
+
+/// Record for an event at `t` inside a volume.
+fn volume_hit(r: &Ray, t: f64, material: Arc<dyn Material>) -> HitRecord {
+    HitRecord {
+        p: r.at(t),
+        t,
+        u: 0.0,
+        v: 0.0,
+        // volumes have no surface, so any normal will do
+        normal: Vec3(1., 0., 0.),
+        front_face: true,
+        material,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_constant_medium() {
+    use crate::sphere::Sphere;
+
+    let white = Vec3(1., 1., 1.);
+    let fog = |density| {
+        let boundary = Sphere::obj(Vec3::zero(), 1.0, Isotropic::obj(white));
+        ConstantMedium::new(boundary, density, white)
+    };
+    let r = Ray {
+        origin: Vec3(0., 0., 5.),
+        direction: Vec3(0., 0., -2.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let ray_t = Interval::new(0.001, f64::INFINITY);
+
+    // the fraction passing through a path of length 2 is exp(-2 density)
+    let thin = fog(0.5);
+    let trials = 20_000;
+    let mut through = 0;
+    for _ in 0..trials {
+        match thin.hit(&r, &ray_t) {
+            Some(rec) => {
+                assert!(rec.t > 2.0 && rec.t < 3.0);
+                assert!(rec.p.len() <= 1.0);
+            }
+            None => through += 1,
+        }
+    }
+    let expected = (-1.0f64).exp();
+    assert!((through as f64 / trials as f64 - expected).abs() < 0.02);
+
+    // starting inside only the part ahead of the ray counts
+    let inside = Ray {
+        origin: Vec3::zero(),
+        direction: Vec3(0., 0., -1.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let dense = fog(1e6);
+    let rec = dense.hit(&inside, &ray_t).unwrap();
+    assert!(rec.t < 0.01);
+    assert!(dense.hit(&inside, &Interval::new(0.001, 0.0005)).is_none());
+}
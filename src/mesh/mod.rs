@@ -0,0 +1,230 @@
+//! Indexed triangle meshes and readers for common mesh file formats.
+
+mod obj;
+mod ply;
+mod stl;
+
+pub use obj::{load_obj, load_obj_groups};
+pub use ply::{load_ply, read_ply};
+pub use stl::{load_stl, read_stl};
+
+use super::aabb::Aabb;
+use super::bvh::BvhTree;
+use super::hittable::{Hit, HitRecord};
+use super::interval::Interval;
+use super::material::Material;
+use super::ray::Ray;
+use super::triangle::{hit_record, intersect};
+use super::vec3::{Point, Vec3};
+use std::sync::Arc;
+
+/// Indexed triangle mesh sharing its vertex buffers between faces.
+///
+/// Normals and surface coordinates, when present, are per vertex and
+/// indexed like the positions. Faces are wound counter clockwise seen from
+/// the front and are intersected through one BVH over the whole mesh.
+pub struct TriangleMesh {
+    positions: Vec<Point>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    indices: Vec<[u32; 3]>,
+    materials: Vec<Arc<dyn Material>>,
+    /// index into `materials` per face, or empty when there is only one
+    face_materials: Vec<u32>,
+    tree: BvhTree,
+}
+
+impl TriangleMesh {
+    /// Panics if an index is out of range.
+    pub fn new(positions: Vec<Point>, indices: Vec<[u32; 3]>, material: Arc<dyn Material>) -> Self {
+        assert!(
+            indices
+                .iter()
+                .flatten()
+                .all(|&i| (i as usize) < positions.len()),
+            "vertex index out of range"
+        );
+        let boxes: Vec<Aabb> = indices
+            .iter()
+            .map(|face| {
+                let bbox = face.iter().fold(Aabb::empty(), |bbox, &i| {
+                    bbox.enclose(positions[i as usize])
+                });
+                Aabb::new(bbox.x, bbox.y, bbox.z)
+            })
+            .collect();
+        let tree = BvhTree::build(&boxes);
+        Self {
+            positions,
+            normals: vec![],
+            uvs: vec![],
+            indices,
+            materials: vec![material],
+            face_materials: vec![],
+            tree,
+        }
+    }
+
+    pub fn obj(
+        positions: Vec<Point>,
+        indices: Vec<[u32; 3]>,
+        material: Arc<dyn Material>,
+    ) -> Box<dyn Hit> {
+        Box::new(Self::new(positions, indices, material))
+    }
+
+    /// Per-vertex normals for smooth shading. Panics unless there is one per
+    /// position.
+    pub fn with_normals(mut self, normals: Vec<Vec3>) -> Self {
+        assert_eq!(normals.len(), self.positions.len(), "normal count mismatch");
+        self.normals = normals;
+        self
+    }
+
+    /// Per-vertex surface coordinates. Panics unless there is one per
+    /// position.
+    pub fn with_uvs(mut self, uvs: Vec<(f64, f64)>) -> Self {
+        assert_eq!(uvs.len(), self.positions.len(), "uv count mismatch");
+        self.uvs = uvs;
+        self
+    }
+
+    /// Replaces the single material with a palette and one palette index per
+    /// face. Panics if the ids do not match the faces or the palette.
+    pub fn with_face_materials(mut self, materials: Vec<Arc<dyn Material>>, ids: Vec<u32>) -> Self {
+        assert_eq!(
+            ids.len(),
+            self.indices.len(),
+            "face material count mismatch"
+        );
+        assert!(
+            ids.iter().all(|&id| (id as usize) < materials.len()),
+            "material id out of range"
+        );
+        self.materials = materials;
+        self.face_materials = ids;
+        self
+    }
+
+    pub fn positions(&self) -> &[Point] {
+        &self.positions
+    }
+
+    pub fn indices(&self) -> &[[u32; 3]] {
+        &self.indices
+    }
+
+    /// number of triangles
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    fn hit_face(&self, face: usize, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let [a, b, c] = self.indices[face].map(|i| i as usize);
+        let p = [&self.positions[a], &self.positions[b], &self.positions[c]];
+        let (t, weights) = intersect(p, r, ray_t)?;
+        let normals = (!self.normals.is_empty())
+            .then(|| [&self.normals[a], &self.normals[b], &self.normals[c]]);
+        let uvs = if self.uvs.is_empty() {
+            [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]
+        } else {
+            [self.uvs[a], self.uvs[b], self.uvs[c]]
+        };
+        let material = match self.face_materials.get(face) {
+            Some(&id) => &self.materials[id as usize],
+            None => &self.materials[0],
+        };
+        Some(hit_record(
+            p,
+            normals,
+            uvs,
+            t,
+            weights,
+            r,
+            Arc::clone(material),
+        ))
+    }
+}
+
+impl Hit for TriangleMesh {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        self.tree
+            .hit(r, ray_t, |face, ray_t| self.hit_face(face, r, ray_t))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.tree.bounding_box()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_mesh_matches_triangles() {
+    use crate::hittable::HittableList;
+    use crate::material::Lambertian;
+    use crate::triangle::Triangle;
+
+    // a bumpy height field over a grid
+    let n = 12;
+    let mut positions = vec![];
+    for j in 0..=n {
+        for i in 0..=n {
+            let (x, z) = (i as f64 - 6.0, j as f64 - 6.0);
+            positions.push(Vec3(x, (0.7 * x).sin() * (0.5 * z).cos(), z));
+        }
+    }
+    let mut indices = vec![];
+    for j in 0..n {
+        for i in 0..n {
+            let v = (j * (n + 1) + i) as u32;
+            let w = v + n as u32 + 1;
+            indices.push([v, w, v + 1]);
+            indices.push([v + 1, w, w + 1]);
+        }
+    }
+    let red = Lambertian::obj(Vec3(0.8, 0.1, 0.1));
+    let blue = Lambertian::obj(Vec3(0.1, 0.1, 0.8));
+    let mut list = HittableList::new();
+    for [a, b, c] in &indices {
+        let [a, b, c] = [a, b, c].map(|&i| positions[i as usize]);
+        list.add(Triangle::obj(a, b, c, red.clone()));
+    }
+    let ids = (0..indices.len() as u32).map(|i| i % 2).collect();
+    let mesh = TriangleMesh::new(positions, indices, red.clone())
+        .with_face_materials(vec![red.clone(), blue.clone()], ids);
+    assert_eq!(mesh.len(), 2 * n * n);
+
+    let ray_t = Interval::new(0.001, f64::MAX);
+    for _ in 0..500 {
+        let r = Ray {
+            origin: Vec3::random_mm(-8.0, 8.0) + Vec3(0., 10., 0.),
+            direction: Vec3::random_mm(-1.0, 1.0) - Vec3(0., 1.5, 0.),
+            time: 0.0,
+            wavelength: None,
+        };
+        let expected = (&list).hit(&r, &ray_t).map(|h| (h.t, h.u, h.v, h.normal));
+        let actual = mesh.hit(&r, &ray_t);
+        assert_eq!(expected, actual.as_ref().map(|h| (h.t, h.u, h.v, h.normal)));
+    }
+
+    let down = Ray {
+        origin: Vec3(-5.9, 5., -5.5),
+        direction: Vec3(0., -1., 0.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let hit = mesh.hit(&down, &ray_t).expect("hits the first face");
+    assert!(Arc::ptr_eq(&hit.material, &red));
+    let down = Ray {
+        origin: Vec3(-5.1, 5., -5.5),
+        direction: Vec3(0., -1., 0.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let hit = mesh.hit(&down, &ray_t).expect("hits the second face");
+    assert!(Arc::ptr_eq(&hit.material, &blue));
+}
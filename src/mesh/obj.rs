@@ -0,0 +1,537 @@
+use super::TriangleMesh;
+use crate::material::{Dielectric, DiffuseLight, Lambertian, Material, Metal};
+use crate::texture::{ImageTexture, Texture};
+use crate::vec3::{Color, Point, Vec3};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("obj: {msg}"))
+}
+
+/// Loads a Wavefront OBJ file as one mesh, with the materials of the
+/// `mtllib` files it references. Faces without a `usemtl`, or whose
+/// material is missing from the libraries, get a light gray `Lambertian`.
+pub fn load_obj(path: impl AsRef<Path>) -> Result<TriangleMesh, std::io::Error> {
+    let obj = read_obj_file(path.as_ref())?;
+    let faces: Vec<usize> = (0..obj.faces.len()).collect();
+    Ok(obj.mesh(&faces))
+}
+
+/// Like `load_obj`, but returns a separate mesh for each `g` or `o` group
+/// that has faces, in file order. Faces before the first group are named
+/// `default`.
+pub fn load_obj_groups(
+    path: impl AsRef<Path>,
+) -> Result<Vec<(String, TriangleMesh)>, std::io::Error> {
+    let obj = read_obj_file(path.as_ref())?;
+    let mut members = vec![vec![]; obj.groups.len()];
+    for (face, &group) in obj.face_groups.iter().enumerate() {
+        members[group].push(face);
+    }
+    Ok(obj
+        .groups
+        .iter()
+        .zip(members)
+        .filter(|(_, faces)| !faces.is_empty())
+        .map(|(name, faces)| (name.clone(), obj.mesh(&faces)))
+        .collect())
+}
+
+fn read_obj_file(path: &Path) -> Result<Obj, std::io::Error> {
+    let text = std::fs::read_to_string(path)?;
+    parse_obj(&text, path.parent().unwrap_or(Path::new(".")))
+}
+
+/// An OBJ file with its vertices unified, so each distinct
+/// position/uv/normal combination is one mesh vertex.
+struct Obj {
+    positions: Vec<Point>,
+    uvs: Vec<(f64, f64)>,
+    normals: Vec<Vec3>,
+    has_uvs: bool,
+    has_normals: bool,
+    faces: Vec<[u32; 3]>,
+    /// palette index per face, 0 being the default material
+    face_materials: Vec<u32>,
+    face_groups: Vec<usize>,
+    materials: Vec<Arc<dyn Material>>,
+    groups: Vec<String>,
+}
+
+impl Obj {
+    /// Mesh of the given faces, keeping only the vertices they use.
+    fn mesh(&self, faces: &[usize]) -> TriangleMesh {
+        let mut remap = HashMap::new();
+        let mut used = vec![];
+        let indices = faces
+            .iter()
+            .map(|&f| {
+                self.faces[f].map(|i| {
+                    *remap.entry(i).or_insert_with(|| {
+                        used.push(i as usize);
+                        (used.len() - 1) as u32
+                    })
+                })
+            })
+            .collect();
+        let positions = used.iter().map(|&i| self.positions[i]).collect();
+        let ids = faces.iter().map(|&f| self.face_materials[f]).collect();
+        let mut mesh = TriangleMesh::new(positions, indices, Arc::clone(&self.materials[0]))
+            .with_face_materials(self.materials.clone(), ids);
+        if self.has_normals {
+            mesh = mesh.with_normals(used.iter().map(|&i| self.normals[i]).collect());
+        }
+        if self.has_uvs {
+            mesh = mesh.with_uvs(used.iter().map(|&i| self.uvs[i]).collect());
+        }
+        mesh
+    }
+}
+
+/// Where a statement came from, for error messages.
+#[derive(Clone, Copy)]
+struct Loc<'a> {
+    /// the material library name, or empty for the OBJ file itself
+    file: &'a str,
+    line: usize,
+}
+
+impl std::fmt::Display for Loc<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.file.is_empty() {
+            write!(f, "line {}", self.line)
+        } else {
+            write!(f, "{} line {}", self.file, self.line)
+        }
+    }
+}
+
+fn floats<const N: usize>(args: &[&str], line: Loc) -> Result<[f64; N], std::io::Error> {
+    let mut out = [0.0; N];
+    if args.len() < N {
+        return Err(invalid(&format!("{line}: expected {N} numbers")));
+    }
+    for (o, a) in out.iter_mut().zip(args) {
+        *o = a
+            .parse()
+            .map_err(|_| invalid(&format!("{line}: bad number '{a}'")))?;
+    }
+    Ok(out)
+}
+
+/// Resolves a 1-based or negative (relative to the end) OBJ index.
+fn resolve(index: &str, count: usize, line: Loc) -> Result<usize, std::io::Error> {
+    let i: i64 = index
+        .parse()
+        .map_err(|_| invalid(&format!("{line}: bad index '{index}'")))?;
+    let resolved = if i < 0 { count as i64 + i } else { i - 1 };
+    if i == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(invalid(&format!("{line}: index {i} out of range")));
+    }
+    Ok(resolved as usize)
+}
+
+fn parse_obj(text: &str, dir: &Path) -> Result<Obj, std::io::Error> {
+    let mut positions = vec![];
+    let mut uvs = vec![];
+    let mut normals = vec![];
+    let mut library: HashMap<String, Arc<dyn Material>> = HashMap::new();
+    let mut textures = HashMap::new();
+
+    let mut obj = Obj {
+        positions: vec![],
+        uvs: vec![],
+        normals: vec![],
+        has_uvs: false,
+        has_normals: false,
+        faces: vec![],
+        face_materials: vec![],
+        face_groups: vec![],
+        materials: vec![Lambertian::obj(Vec3(0.8, 0.8, 0.8))],
+        groups: vec!["default".to_string()],
+    };
+    let mut palette: HashMap<String, u32> = HashMap::new();
+    let mut vertices: HashMap<(usize, Option<usize>, Option<usize>), u32> = HashMap::new();
+    let mut material = 0;
+    let mut group = 0;
+
+    for (n, raw) in text.lines().enumerate() {
+        let line = Loc {
+            file: "",
+            line: n + 1,
+        };
+        let raw = raw.split('#').next().unwrap_or("");
+        let mut words = raw.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let args: Vec<&str> = words.collect();
+        match keyword {
+            "v" => {
+                let [x, y, z] = floats(&args, line)?;
+                positions.push(Vec3(x, y, z));
+            }
+            "vt" => {
+                let u = floats::<1>(&args, line)?[0];
+                let v = if args.len() > 1 {
+                    floats::<2>(&args, line)?[1]
+                } else {
+                    0.0
+                };
+                uvs.push((u, v));
+            }
+            "vn" => {
+                let [x, y, z] = floats(&args, line)?;
+                normals.push(Vec3(x, y, z));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(invalid(&format!("{line}: face needs 3 vertices")));
+                }
+                let mut corners = Vec::with_capacity(args.len());
+                for corner in &args {
+                    let mut parts = corner.split('/');
+                    let v = resolve(parts.next().unwrap_or(""), positions.len(), line)?;
+                    let vt = match parts.next() {
+                        Some("") | None => None,
+                        Some(i) => Some(resolve(i, uvs.len(), line)?),
+                    };
+                    let vn = match parts.next() {
+                        Some("") | None => None,
+                        Some(i) => Some(resolve(i, normals.len(), line)?),
+                    };
+                    obj.has_uvs |= vt.is_some();
+                    obj.has_normals |= vn.is_some();
+                    let index = *vertices.entry((v, vt, vn)).or_insert_with(|| {
+                        obj.positions.push(positions[v]);
+                        obj.uvs.push(vt.map_or((0.0, 0.0), |i| uvs[i]));
+                        // a zero normal makes the triangle fall back to its
+                        // geometric normal
+                        obj.normals.push(vn.map_or(Vec3::zero(), |i| normals[i]));
+                        (obj.positions.len() - 1) as u32
+                    });
+                    corners.push(index);
+                }
+                // fan triangulation, fine for the convex polygons OBJ
+                // exporters write
+                for k in 1..corners.len() - 1 {
+                    obj.faces.push([corners[0], corners[k], corners[k + 1]]);
+                    obj.face_materials.push(material);
+                    obj.face_groups.push(group);
+                }
+            }
+            "g" | "o" => {
+                let name = if args.is_empty() {
+                    "default".to_string()
+                } else {
+                    args.join(" ")
+                };
+                group = match obj.groups.iter().position(|g| *g == name) {
+                    Some(g) => g,
+                    None => {
+                        obj.groups.push(name);
+                        obj.groups.len() - 1
+                    }
+                };
+            }
+            "mtllib" => {
+                for file in &args {
+                    let path = dir.join(file);
+                    let text = std::fs::read_to_string(&path).map_err(|e| {
+                        std::io::Error::new(
+                            e.kind(),
+                            format!("obj: cannot read material library {}: {e}", path.display()),
+                        )
+                    })?;
+                    library.extend(parse_mtl(&text, file, dir, &mut textures)?);
+                }
+            }
+            "usemtl" => {
+                let name = args.join(" ");
+                material = match library.get(&name) {
+                    Some(m) => *palette.entry(name).or_insert_with(|| {
+                        obj.materials.push(Arc::clone(m));
+                        (obj.materials.len() - 1) as u32
+                    }),
+                    // exporters often name materials they never wrote out
+                    None => 0,
+                };
+            }
+            // smoothing groups, lines, points and free-form geometry
+            _ => {}
+        }
+    }
+    Ok(obj)
+}
+
+/// The statements of one `newmtl` block that map onto our materials.
+struct MtlEntry {
+    name: String,
+    diffuse: Color,
+    specular: Color,
+    exponent: f64,
+    ior: f64,
+    emission: Color,
+    dissolve: f64,
+    illum: u32,
+    diffuse_map: Option<Arc<dyn Texture>>,
+}
+
+impl MtlEntry {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            diffuse: Vec3(0.8, 0.8, 0.8),
+            specular: Color::zero(),
+            exponent: 0.0,
+            ior: 1.5,
+            emission: Color::zero(),
+            dissolve: 1.0,
+            illum: 2,
+            diffuse_map: None,
+        }
+    }
+
+    /// Picks the closest material: emitters become `DiffuseLight`,
+    /// transparent or refracting ones `Dielectric`, mostly specular ones
+    /// `Metal` and everything else `Lambertian`.
+    fn material(self) -> Arc<dyn Material> {
+        let max = |c: &Color| c.0.max(c.1).max(c.2);
+        if max(&self.emission) > 0.0 {
+            DiffuseLight::obj(self.emission)
+        } else if self.dissolve < 1.0 || matches!(self.illum, 4 | 6 | 7 | 9) {
+            Dielectric::obj(self.ior)
+        } else if self.illum == 3 || max(&self.specular) > max(&self.diffuse) {
+            // Phong exponent to an equivalent roughness
+            let roughness = (2.0 / (self.exponent.max(0.0) + 2.0)).sqrt();
+            Metal::obj(self.specular, roughness)
+        } else {
+            match self.diffuse_map {
+                Some(texture) => Lambertian::obj(texture),
+                None => Lambertian::obj(self.diffuse),
+            }
+        }
+    }
+}
+
+fn parse_mtl(
+    text: &str,
+    name: &str,
+    dir: &Path,
+    textures: &mut HashMap<PathBuf, Arc<dyn Texture>>,
+) -> Result<HashMap<String, Arc<dyn Material>>, std::io::Error> {
+    let mut materials = HashMap::new();
+    let mut current: Option<MtlEntry> = None;
+    for (n, raw) in text.lines().enumerate() {
+        let line = Loc {
+            file: name,
+            line: n + 1,
+        };
+        let raw = raw.split('#').next().unwrap_or("");
+        let mut words = raw.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let args: Vec<&str> = words.collect();
+        if keyword == "newmtl" {
+            if let Some(entry) = current.take() {
+                materials.insert(entry.name.clone(), entry.material());
+            }
+            current = Some(MtlEntry::new(args.join(" ")));
+            continue;
+        }
+        let Some(entry) = current.as_mut() else {
+            continue;
+        };
+        match keyword {
+            "Kd" => entry.diffuse = color(&args, line)?,
+            "Ks" => entry.specular = color(&args, line)?,
+            "Ke" => entry.emission = color(&args, line)?,
+            "Ns" => entry.exponent = floats::<1>(&args, line)?[0],
+            "Ni" => entry.ior = floats::<1>(&args, line)?[0],
+            "d" => entry.dissolve = floats::<1>(&args, line)?[0],
+            "Tr" => entry.dissolve = 1.0 - floats::<1>(&args, line)?[0],
+            "illum" => entry.illum = floats::<1>(&args, line)?[0] as u32,
+            "map_Kd" => {
+                // options like `-s 1 1 1` come first, the file name last
+                let Some(file) = args.last() else {
+                    return Err(invalid(&format!("{line}: map_Kd without a file")));
+                };
+                let path = dir.join(file);
+                let texture = match textures.get(&path) {
+                    Some(texture) => Arc::clone(texture),
+                    None => {
+                        let texture: Arc<dyn Texture> =
+                            Arc::new(ImageTexture::load(&path).map_err(|e| {
+                                std::io::Error::new(
+                                    e.kind(),
+                                    format!("obj: cannot load texture {}: {e}", path.display()),
+                                )
+                            })?);
+                        textures.insert(path, Arc::clone(&texture));
+                        texture
+                    }
+                };
+                entry.diffuse_map = Some(texture);
+            }
+            _ => {}
+        }
+    }
+    if let Some(entry) = current {
+        materials.insert(entry.name.clone(), entry.material());
+    }
+    Ok(materials)
+}
+
+/// An `r g b` triple, or a single gray value.
+fn color(args: &[&str], line: Loc) -> Result<Color, std::io::Error> {
+    if args.len() >= 3 {
+        let [r, g, b] = floats(args, line)?;
+        Ok(Vec3(r, g, b))
+    } else {
+        let [g] = floats(args, line)?;
+        Ok(Vec3(g, g, g))
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_obj() {
+    use crate::hittable::Hit;
+    use crate::interval::Interval;
+    use crate::ray::Ray;
+
+    let text = "\
+# a unit square split in two groups, with one quad face
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 2 0 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+g left
+f 1/1/1 2/2/1 3/3/1 4/4/1
+g right
+f -4//1 -1//1 -3//1
+";
+    let obj = parse_obj(text, Path::new(".")).unwrap();
+    assert_eq!(obj.faces.len(), 3);
+    assert_eq!(obj.groups, ["default", "left", "right"]);
+    assert_eq!(obj.face_groups, [1, 1, 2]);
+    // the right face shares positions but not uvs with the left ones
+    assert_eq!(obj.positions.len(), 7);
+
+    let faces: Vec<usize> = (0..2).collect();
+    let left = obj.mesh(&faces);
+    assert_eq!(left.len(), 2);
+    assert_eq!(left.positions().len(), 4);
+    let r = Ray {
+        origin: Vec3(0.25, 0.75, 1.),
+        direction: Vec3(0., 0., -1.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let hit = left.hit(&r, &Interval::new(0.001, f64::MAX)).unwrap();
+    assert!((hit.u - 0.25).abs() < 1e-12 && (hit.v - 0.75).abs() < 1e-12);
+    assert_eq!(hit.normal, Vec3(0., 0., 1.));
+
+    let err = parse_obj("v 0 0 0\nf 1 2 3\n", Path::new("."))
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "obj: line 2: index 2 out of range");
+    // materials missing from the libraries fall back to the default
+    let obj = parse_obj(
+        "v 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl steel\nf 1 2 3\n",
+        Path::new("."),
+    )
+    .unwrap();
+    assert_eq!(obj.face_materials, [0]);
+    assert_eq!(obj.materials.len(), 1);
+}
+
+#[cfg(test)]
+#[test]
+fn test_parse_mtl() {
+    use crate::hittable::HitRecord;
+    use crate::ray::Ray;
+    use crate::vec3::unit_vector;
+
+    let text = "\
+newmtl glass
+Ni 1.45
+d 0.2
+newmtl gold
+Kd 0.1 0.1 0.1
+Ks 1.0 0.8 0.3
+Ns 200
+newmtl lamp
+Ke 5 5 5
+newmtl paint
+Kd 0.5
+";
+    let materials = parse_mtl(text, "test.mtl", Path::new("."), &mut HashMap::new()).unwrap();
+    assert_eq!(materials.len(), 4);
+
+    // tell the materials apart by how they treat a ray straight down
+    let r = Ray {
+        origin: Vec3(0., 1., 0.),
+        direction: Vec3(0., -1., 0.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let up = Vec3(0., 1., 0.);
+    let hit = |name: &str| {
+        let material = Arc::clone(&materials[name]);
+        HitRecord::with_normal(Vec3::zero(), &r, up, 1.0, material)
+    };
+    for name in ["glass", "gold", "lamp", "paint"] {
+        let rec = hit(name);
+        let glows = rec.material.emitted(&r, &rec) != Color::zero();
+        assert_eq!(glows, name == "lamp", "{name}");
+    }
+    let lamp = hit("lamp");
+    assert_eq!(lamp.material.emitted(&r, &lamp), Vec3(5., 5., 5.));
+    assert!(lamp.material.scatter(&r, &lamp).is_none());
+
+    // glass refracts most of the light through, untinted
+    let glass = hit("glass");
+    let through = (0..100)
+        .filter(|_| {
+            let scatter = glass.material.scatter(&r, &glass).unwrap();
+            assert_eq!(scatter.color_attenuation, Color::one());
+            scatter.ray.direction.1 < 0.0
+        })
+        .count();
+    assert!(through > 80);
+
+    // gold reflects close to the mirror direction, tinted by Ks, and is
+    // specular so it reports no scattering density
+    let gold = hit("gold");
+    let scatter = gold.material.scatter(&r, &gold).unwrap();
+    assert_eq!(scatter.color_attenuation, Vec3(1.0, 0.8, 0.3));
+    let direction = unit_vector(&scatter.ray.direction);
+    assert!(direction.1 > 0.8);
+    assert_eq!(gold.material.scattering_pdf(&r, &gold, &up), 0.0);
+
+    // paint is diffuse with its Kd
+    let paint = hit("paint");
+    let scatter = paint.material.scatter(&r, &paint).unwrap();
+    assert_eq!(scatter.color_attenuation, Vec3(0.5, 0.5, 0.5));
+    assert!(paint.material.scattering_pdf(&r, &paint, &up) > 0.0);
+
+    let err = parse_mtl(
+        "newmtl x\nKd red\n",
+        "test.mtl",
+        Path::new("."),
+        &mut HashMap::new(),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(err.to_string(), "obj: test.mtl line 2: bad number 'red'");
+}
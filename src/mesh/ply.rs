@@ -0,0 +1,451 @@
+use super::TriangleMesh;
+use crate::color::srgb_to_linear;
+use crate::material::{Lambertian, Material};
+use crate::vec3::{Color, Vec3};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("ply: {msg}"))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Result<Self, std::io::Error> {
+        Ok(match name {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => return Err(invalid(&format!("unknown property type '{name}'"))),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// full scale of the type, for normalizing integer colors
+    fn full_scale(self) -> f64 {
+        match self {
+            Self::I8 => i8::MAX as f64,
+            Self::U8 => u8::MAX as f64,
+            Self::I16 => i16::MAX as f64,
+            Self::U16 => u16::MAX as f64,
+            Self::I32 => i32::MAX as f64,
+            Self::U32 => u32::MAX as f64,
+            Self::F32 | Self::F64 => 1.0,
+        }
+    }
+}
+
+enum Property {
+    Scalar {
+        name: String,
+        ty: Scalar,
+    },
+    List {
+        name: String,
+        count: Scalar,
+        item: Scalar,
+    },
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+/// Reads property values from the data following the header.
+struct Body<'a> {
+    data: &'a [u8],
+    pos: usize,
+    format: Format,
+}
+
+impl Body<'_> {
+    fn value(&mut self, ty: Scalar) -> Result<f64, std::io::Error> {
+        if self.format == Format::Ascii {
+            while self.data.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+                self.pos += 1;
+            }
+            let start = self.pos;
+            while self
+                .data
+                .get(self.pos)
+                .is_some_and(|b| !b.is_ascii_whitespace())
+            {
+                self.pos += 1;
+            }
+            if start == self.pos {
+                return Err(invalid("unexpected end of data"));
+            }
+            let token = &self.data[start..self.pos];
+            return std::str::from_utf8(token)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(|| {
+                    invalid(&format!("bad number '{}'", String::from_utf8_lossy(token)))
+                });
+        }
+
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + ty.size())
+            .ok_or_else(|| invalid("unexpected end of data"))?;
+        self.pos += ty.size();
+        // reverse big endian values so everything decodes as little endian
+        let little = self.format == Format::LittleEndian;
+        fn le<const N: usize>(bytes: &[u8], little: bool) -> [u8; N] {
+            let mut a: [u8; N] = bytes.try_into().unwrap();
+            if !little {
+                a.reverse();
+            }
+            a
+        }
+        Ok(match ty {
+            Scalar::I8 => bytes[0] as i8 as f64,
+            Scalar::U8 => bytes[0] as f64,
+            Scalar::I16 => i16::from_le_bytes(le(bytes, little)) as f64,
+            Scalar::U16 => u16::from_le_bytes(le(bytes, little)) as f64,
+            Scalar::I32 => i32::from_le_bytes(le(bytes, little)) as f64,
+            Scalar::U32 => u32::from_le_bytes(le(bytes, little)) as f64,
+            Scalar::F32 => f32::from_le_bytes(le(bytes, little)) as f64,
+            Scalar::F64 => f64::from_le_bytes(le(bytes, little)),
+        })
+    }
+
+    /// Reads one list property, returning its items.
+    fn list(&mut self, count: Scalar, item: Scalar) -> Result<Vec<f64>, std::io::Error> {
+        let n = self.value(count)?;
+        if n < 0.0 || n.fract() != 0.0 {
+            return Err(invalid(&format!("bad list length {n}")));
+        }
+        (0..n as usize).map(|_| self.value(item)).collect()
+    }
+}
+
+fn parse_header(data: &[u8]) -> Result<(Format, Vec<Element>, usize), std::io::Error> {
+    let mut pos = 0;
+    let mut next_line = || {
+        let rest = data.get(pos..).filter(|r| !r.is_empty())?;
+        let end = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+        pos += (end + 1).min(rest.len());
+        Some(String::from_utf8_lossy(&rest[..end]).trim_end().to_string())
+    };
+    if next_line().as_deref() != Some("ply") {
+        return Err(invalid("missing 'ply' magic number"));
+    }
+
+    let mut format = None;
+    let mut elements: Vec<Element> = vec![];
+    loop {
+        let Some(line) = next_line() else {
+            return Err(invalid("missing end_header"));
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["end_header"] => break,
+            ["format", kind, _version] => {
+                format = Some(match *kind {
+                    "ascii" => Format::Ascii,
+                    "binary_little_endian" => Format::LittleEndian,
+                    "binary_big_endian" => Format::BigEndian,
+                    _ => return Err(invalid(&format!("unsupported format '{kind}'"))),
+                });
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| invalid(&format!("bad count for element '{name}'")))?,
+                properties: vec![],
+            }),
+            ["property", rest @ ..] => {
+                let Some(element) = elements.last_mut() else {
+                    return Err(invalid("property before any element"));
+                };
+                let property = match rest {
+                    ["list", count, item, name] => Property::List {
+                        name: name.to_string(),
+                        count: Scalar::parse(count)?,
+                        item: Scalar::parse(item)?,
+                    },
+                    [ty, name] => Property::Scalar {
+                        name: name.to_string(),
+                        ty: Scalar::parse(ty)?,
+                    },
+                    _ => return Err(invalid(&format!("malformed header line '{line}'"))),
+                };
+                element.properties.push(property);
+            }
+            _ => return Err(invalid(&format!("malformed header line '{line}'"))),
+        }
+    }
+    let format = format.ok_or_else(|| invalid("missing format line"))?;
+    Ok((format, elements, pos))
+}
+
+/// Reads an ASCII or binary (little or big endian) Stanford PLY mesh.
+///
+/// Vertices need `x`, `y` and `z`; `nx`, `ny`, `nz` become smooth shading
+/// normals and `u`, `v` (or `s`, `t`) surface coordinates. Polygons are
+/// triangulated as fans. When vertices carry `red`, `green` and `blue`,
+/// each face gets a `Lambertian` of its averaged vertex color in place of
+/// `material`.
+pub fn read_ply(
+    mut f: impl std::io::Read,
+    material: Arc<dyn Material>,
+) -> Result<TriangleMesh, std::io::Error> {
+    let mut data = Vec::new();
+    f.read_to_end(&mut data)?;
+    let (format, elements, start) = parse_header(&data)?;
+    let mut body = Body {
+        data: &data,
+        pos: start,
+        format,
+    };
+
+    let mut positions = vec![];
+    let mut normals = vec![];
+    let mut uvs = vec![];
+    let mut colors = vec![];
+    let mut polygons: Vec<Vec<f64>> = vec![];
+    let mut saw_vertices = false;
+    let mut saw_faces = false;
+
+    for element in &elements {
+        let field = |names: &[&str]| {
+            element.properties.iter().position(
+                |p| matches!(p, Property::Scalar { name, .. } if names.contains(&name.as_str())),
+            )
+        };
+        let is_vertex = element.name == "vertex";
+        let is_face = element.name == "face";
+        let xyz = [field(&["x"]), field(&["y"]), field(&["z"])];
+        let nxyz = [field(&["nx"]), field(&["ny"]), field(&["nz"])];
+        let uv = [
+            field(&["u", "s", "texture_u", "texture_s"]),
+            field(&["v", "t", "texture_v", "texture_t"]),
+        ];
+        let rgb = [field(&["red"]), field(&["green"]), field(&["blue"])];
+        if is_vertex {
+            if xyz.iter().any(Option::is_none) {
+                return Err(invalid("vertex element without x, y and z"));
+            }
+            saw_vertices = true;
+        }
+        let indices = element.properties.iter().position(|p| {
+            matches!(p, Property::List { name, .. } if name == "vertex_indices" || name == "vertex_index")
+        });
+        if is_face {
+            if indices.is_none() {
+                return Err(invalid("face element without vertex_indices"));
+            }
+            saw_faces = true;
+        }
+
+        let mut values = vec![0.0; element.properties.len()];
+        for _ in 0..element.count {
+            for (k, property) in element.properties.iter().enumerate() {
+                match property {
+                    Property::Scalar { ty, .. } => values[k] = body.value(*ty)?,
+                    Property::List { count, item, .. } => {
+                        let items = body.list(*count, *item)?;
+                        if is_face && Some(k) == indices {
+                            polygons.push(items);
+                        }
+                    }
+                }
+            }
+            if !is_vertex {
+                continue;
+            }
+            let get =
+                |[a, b, c]: [Option<usize>; 3]| Some(Vec3(values[a?], values[b?], values[c?]));
+            positions.push(get(xyz).unwrap());
+            if let Some(n) = get(nxyz) {
+                normals.push(n);
+            }
+            if let [Some(u), Some(v)] = uv {
+                uvs.push((values[u], values[v]));
+            }
+            if let Some(c) = get(rgb) {
+                let Property::Scalar { ty, .. } = element.properties[rgb[0].unwrap()] else {
+                    unreachable!()
+                };
+                colors.push(c / ty.full_scale());
+            }
+        }
+    }
+    if !saw_vertices {
+        return Err(invalid("no vertex element"));
+    }
+    if !saw_faces {
+        return Err(invalid("no face element, point clouds are not supported"));
+    }
+
+    let mut faces = vec![];
+    for polygon in &polygons {
+        if polygon.len() < 3 {
+            return Err(invalid(&format!("face with {} vertices", polygon.len())));
+        }
+        let mut corners = Vec::with_capacity(polygon.len());
+        for &i in polygon {
+            if i < 0.0 || i.fract() != 0.0 || i >= positions.len() as f64 {
+                return Err(invalid(&format!("vertex index {i} out of range")));
+            }
+            corners.push(i as u32);
+        }
+        for k in 1..corners.len() - 1 {
+            faces.push([corners[0], corners[k], corners[k + 1]]);
+        }
+    }
+
+    // per-face colors, shared between faces that end up with the same
+    // 8 bit value
+    let mut palette = None;
+    if !colors.is_empty() {
+        let mut materials = vec![];
+        let mut lookup: HashMap<[u8; 3], u32> = HashMap::new();
+        let ids = faces
+            .iter()
+            .map(|face| {
+                let c = face
+                    .iter()
+                    .fold(Color::zero(), |c, &i| c + colors[i as usize])
+                    / 3.0;
+                let key = [c.0, c.1, c.2].map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8);
+                *lookup.entry(key).or_insert_with(|| {
+                    let [r, g, b] = key.map(|x| srgb_to_linear(x as f64 / 255.0));
+                    materials.push(Lambertian::obj(Vec3(r, g, b)));
+                    (materials.len() - 1) as u32
+                })
+            })
+            .collect();
+        palette = Some((materials, ids));
+    }
+
+    let mut mesh = TriangleMesh::new(positions, faces, material);
+    if !normals.is_empty() {
+        mesh = mesh.with_normals(normals);
+    }
+    if !uvs.is_empty() {
+        mesh = mesh.with_uvs(uvs);
+    }
+    if let Some((materials, ids)) = palette {
+        mesh = mesh.with_face_materials(materials, ids);
+    }
+    Ok(mesh)
+}
+
+pub fn load_ply(
+    path: impl AsRef<Path>,
+    material: Arc<dyn Material>,
+) -> Result<TriangleMesh, std::io::Error> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    read_ply(file, material)
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_ply() {
+    use crate::hittable::Hit;
+
+    let material = Lambertian::obj(Vec3(0.5, 0.5, 0.5));
+    let ascii = "\
+ply
+format ascii 1.0
+comment a unit square as one quad
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 255 0 0
+1 1 0 255 0 0
+0 1 0 255 0 0
+4 0 1 2 3
+";
+    let mesh = read_ply(ascii.as_bytes(), material.clone()).unwrap();
+    assert_eq!(mesh.len(), 2);
+    assert_eq!(mesh.positions()[2], Vec3(1., 1., 0.));
+
+    // the same square as binary, in both byte orders
+    for (format, big) in [("binary_little_endian", false), ("binary_big_endian", true)] {
+        let mut data = format!(
+            "ply\nformat {format} 1.0\nelement vertex 4\nproperty double x\n\
+             property double y\nproperty double z\nelement face 1\n\
+             property list uchar uint vertex_indices\nend_header\n"
+        )
+        .into_bytes();
+        for p in [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]] {
+            for x in p {
+                data.extend(if big {
+                    f64::to_be_bytes(x)
+                } else {
+                    f64::to_le_bytes(x)
+                });
+            }
+        }
+        data.push(4);
+        for i in 0u32..4 {
+            data.extend(if big {
+                i.to_be_bytes()
+            } else {
+                i.to_le_bytes()
+            });
+        }
+        let binary = read_ply(data.as_slice(), material.clone()).unwrap();
+        assert_eq!(binary.positions(), mesh.positions());
+        assert_eq!(binary.indices(), mesh.indices());
+        assert_eq!(binary.bounding_box(), mesh.bounding_box());
+
+        data.truncate(data.len() - 2);
+        let err = read_ply(data.as_slice(), material.clone()).err().unwrap();
+        assert_eq!(err.to_string(), "ply: unexpected end of data");
+    }
+
+    let bad = ascii.replace("4 0 1 2 3", "3 0 1 7");
+    let err = read_ply(bad.as_bytes(), material.clone()).err().unwrap();
+    assert_eq!(err.to_string(), "ply: vertex index 7 out of range");
+    let bad = ascii.replace("property float z", "property quad z");
+    let err = read_ply(bad.as_bytes(), material).err().unwrap();
+    assert_eq!(err.to_string(), "ply: unknown property type 'quad'");
+}
@@ -0,0 +1,204 @@
+use super::TriangleMesh;
+use crate::material::Material;
+use crate::vec3::{Point, Vec3};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("stl: {msg}"))
+}
+
+/// Reads an ASCII or binary STL file.
+///
+/// STL stores every triangle with its own corners; identical corners are
+/// merged so the result is an indexed mesh. The stored facet normals are
+/// ignored in favor of the winding, as they are frequently wrong.
+pub fn read_stl(
+    mut f: impl std::io::Read,
+    material: Arc<dyn Material>,
+) -> Result<TriangleMesh, std::io::Error> {
+    let mut data = Vec::new();
+    f.read_to_end(&mut data)?;
+
+    // binary files may also start with "solid", so trust the size first
+    let binary_count = data
+        .get(80..84)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize);
+    let text = std::str::from_utf8(&data)
+        .ok()
+        .filter(|text| text.trim_start().starts_with("solid"));
+    let triangles = match (binary_count, text) {
+        (Some(n), _) if data.len() == 84 + 50 * n => read_binary(&data[84..], n),
+        (_, Some(text)) => read_ascii(text)?,
+        (Some(n), None) => {
+            return Err(invalid(&format!(
+                "binary file of {} bytes cannot hold {n} triangles",
+                data.len()
+            )));
+        }
+        (None, None) => return Err(invalid("not an STL file")),
+    };
+    Ok(weld(triangles, material))
+}
+
+pub fn load_stl(
+    path: impl AsRef<Path>,
+    material: Arc<dyn Material>,
+) -> Result<TriangleMesh, std::io::Error> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    read_stl(file, material)
+}
+
+/// 50 byte records: normal, three corners, attribute byte count
+fn read_binary(data: &[u8], count: usize) -> Vec<[Point; 3]> {
+    let float =
+        |b: &[u8], k: usize| f32::from_le_bytes(b[4 * k..4 * k + 4].try_into().unwrap()) as f64;
+    data.chunks_exact(50)
+        .take(count)
+        .map(|record| {
+            let corner = |c: usize| {
+                Vec3(
+                    float(record, 3 + 3 * c),
+                    float(record, 4 + 3 * c),
+                    float(record, 5 + 3 * c),
+                )
+            };
+            [corner(0), corner(1), corner(2)]
+        })
+        .collect()
+}
+
+fn read_ascii(text: &str) -> Result<Vec<[Point; 3]>, std::io::Error> {
+    let mut triangles = vec![];
+    let mut corners = Vec::with_capacity(3);
+    let mut in_facet = false;
+    for (n, line) in text.lines().enumerate() {
+        let line_number = n + 1;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.first().copied() {
+            Some("facet") => {
+                if in_facet {
+                    return Err(invalid(&format!("line {line_number}: facet not closed")));
+                }
+                in_facet = true;
+                corners.clear();
+            }
+            Some("vertex") => {
+                if !in_facet {
+                    return Err(invalid(&format!(
+                        "line {line_number}: vertex outside a facet"
+                    )));
+                }
+                let coords: Option<Vec<f64>> = words[1..].iter().map(|w| w.parse().ok()).collect();
+                match coords.as_deref() {
+                    Some(&[x, y, z]) => corners.push(Vec3(x, y, z)),
+                    _ => return Err(invalid(&format!("line {line_number}: bad vertex"))),
+                }
+            }
+            Some("endfacet") => {
+                if !in_facet {
+                    return Err(invalid(&format!(
+                        "line {line_number}: endfacet without facet"
+                    )));
+                }
+                let &[a, b, c] = corners.as_slice() else {
+                    return Err(invalid(&format!(
+                        "line {line_number}: facet with {} vertices",
+                        corners.len()
+                    )));
+                };
+                triangles.push([a, b, c]);
+                in_facet = false;
+            }
+            Some("solid" | "endsolid" | "outer" | "endloop") | None => {}
+            Some(word) => {
+                return Err(invalid(&format!("line {line_number}: unexpected '{word}'")));
+            }
+        }
+    }
+    if in_facet {
+        return Err(invalid("facet not closed at end of file"));
+    }
+    Ok(triangles)
+}
+
+/// Merges bit-identical corners into shared vertices.
+fn weld(triangles: Vec<[Point; 3]>, material: Arc<dyn Material>) -> TriangleMesh {
+    let mut positions = vec![];
+    let mut lookup: HashMap<[u64; 3], u32> = HashMap::new();
+    let indices = triangles
+        .iter()
+        .map(|corners| {
+            corners.map(|p| {
+                let key = [p.0, p.1, p.2].map(f64::to_bits);
+                *lookup.entry(key).or_insert_with(|| {
+                    positions.push(p);
+                    (positions.len() - 1) as u32
+                })
+            })
+        })
+        .collect();
+    TriangleMesh::new(positions, indices, material)
+}
+
+#[cfg(test)]
+#[test]
+fn test_read_stl() {
+    use crate::material::Lambertian;
+
+    let material = Lambertian::obj(Vec3(0.5, 0.5, 0.5));
+    let ascii = "\
+solid square
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 1 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid square
+";
+    let mesh = read_stl(ascii.as_bytes(), material.clone()).unwrap();
+    assert_eq!(mesh.len(), 2);
+    // shared corners are welded
+    assert_eq!(mesh.positions().len(), 4);
+
+    // the same triangles in binary, with a header that starts like ASCII
+    let mut data = b"solid binary".to_vec();
+    data.resize(80, 0);
+    data.extend(2u32.to_le_bytes());
+    for tri in [
+        [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.]],
+        [[0., 0., 0.], [1., 1., 0.], [0., 1., 0.]],
+    ] {
+        data.extend([0f32, 0., 1.].iter().flat_map(|x| x.to_le_bytes()));
+        for corner in tri {
+            data.extend(corner.iter().flat_map(|&x| (x as f32).to_le_bytes()));
+        }
+        data.extend(0u16.to_le_bytes());
+    }
+    let binary = read_stl(data.as_slice(), material.clone()).unwrap();
+    assert_eq!(binary.positions(), mesh.positions());
+    assert_eq!(binary.indices(), mesh.indices());
+
+    data.pop();
+    let err = read_stl(data.as_slice(), material.clone()).err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "stl: binary file of 183 bytes cannot hold 2 triangles"
+    );
+    let bad = ascii.replacen("      vertex 1 0 0\n", "", 1);
+    let err = read_stl(bad.as_bytes(), material.clone()).err().unwrap();
+    assert_eq!(err.to_string(), "stl: line 7: facet with 2 vertices");
+    let bad = ascii.replacen("vertex 1 0 0", "vertex 1 zero 0", 1);
+    let err = read_stl(bad.as_bytes(), material).err().unwrap();
+    assert_eq!(err.to_string(), "stl: line 5: bad vertex");
+}
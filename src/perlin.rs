@@ -0,0 +1,126 @@
+use crate::vec3::{Point, Vec3, dot};
+use rand::SeedableRng;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+const POINT_COUNT: usize = 256;
+
+/// Gradient noise on a unit lattice, reproducible from its seed.
+///
+/// `noise` is zero on lattice points and roughly within `[-1, 1]`
+/// elsewhere; `turbulence` and `fbm` sum octaves of it at doubling
+/// frequencies.
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| {
+                loop {
+                    let v = Vec3(
+                        rng.random_range(-1.0..1.0),
+                        rng.random_range(-1.0..1.0),
+                        rng.random_range(-1.0..1.0),
+                    );
+                    let lensq = v.len_squared();
+                    if 1e-8 < lensq && lensq <= 1.0 {
+                        break v / lensq.sqrt();
+                    }
+                }
+            })
+            .collect();
+        let mut permutation = || {
+            let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+            p.shuffle(&mut rng);
+            p
+        };
+        let (perm_x, perm_y, perm_z) = (permutation(), permutation(), permutation());
+        Self {
+            gradients,
+            perm_x,
+            perm_y,
+            perm_z,
+        }
+    }
+
+    pub fn noise(&self, p: &Point) -> f64 {
+        let (fx, fy, fz) = (p.0.floor(), p.1.floor(), p.2.floor());
+        let (u, v, w) = (p.0 - fx, p.1 - fy, p.2 - fz);
+        let (i, j, k) = (fx as i64, fy as i64, fz as i64);
+
+        // quintic fade keeps the second derivative continuous across cells
+        let fade = |t: f64| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let (uu, vv, ww) = (fade(u), fade(v), fade(w));
+
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let gradient = &self.gradients[self.perm_x[((i + di) & 255) as usize]
+                        ^ self.perm_y[((j + dj) & 255) as usize]
+                        ^ self.perm_z[((k + dk) & 255) as usize]];
+                    let (di, dj, dk) = (di as f64, dj as f64, dk as f64);
+                    let offset = Vec3(u - di, v - dj, w - dk);
+                    accum += (di * uu + (1.0 - di) * (1.0 - uu))
+                        * (dj * vv + (1.0 - dj) * (1.0 - vv))
+                        * (dk * ww + (1.0 - dk) * (1.0 - ww))
+                        * dot(gradient, &offset);
+                }
+            }
+        }
+        accum
+    }
+
+    /// Sum of `|noise|` over `octaves`, each at twice the frequency and half
+    /// the weight of the last. Always non-negative.
+    pub fn turbulence(&self, p: &Point, octaves: u32) -> f64 {
+        let mut accum = 0.0;
+        let mut p = *p;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            accum += weight * self.noise(&p).abs();
+            weight *= 0.5;
+            p *= 2.0;
+        }
+        accum
+    }
+
+    /// Fractal Brownian motion: signed noise summed over `octaves`, scaling
+    /// frequency by `lacunarity` and amplitude by `gain` each octave.
+    pub fn fbm(&self, p: &Point, octaves: u32, lacunarity: f64, gain: f64) -> f64 {
+        let mut accum = 0.0;
+        let mut p = *p;
+        let mut amplitude = 1.0;
+        for _ in 0..octaves {
+            accum += amplitude * self.noise(&p);
+            amplitude *= gain;
+            p *= lacunarity;
+        }
+        accum
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_perlin() {
+    let a = Perlin::new(7);
+    let b = Perlin::new(7);
+    let c = Perlin::new(8);
+    let p = Vec3(1.3, -4.7, 0.25);
+    assert_eq!(a.noise(&p), b.noise(&p));
+    assert_ne!(a.noise(&p), c.noise(&p));
+    // gradient noise vanishes on the lattice
+    assert_eq!(a.noise(&Vec3(3., -2., 5.)), 0.0);
+    for _ in 0..1000 {
+        let p = Vec3::random_mm(-50.0, 50.0);
+        assert!(a.noise(&p).abs() <= 1.0);
+        assert!(a.turbulence(&p, 7) >= 0.0);
+    }
+}
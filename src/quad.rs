@@ -0,0 +1,156 @@
+use super::aabb::Aabb;
+use super::bvh::Bvh;
+use super::hittable::{Hit, HitRecord, HittableList};
+use super::interval::Interval;
+use super::material::Material;
+use super::ray::Ray;
+use super::vec3::{Point, Vec3, cross, dot, unit_vector};
+use std::sync::Arc;
+
+/// Parallelogram spanned by the edges `u` and `v` from the corner `q`.
+///
+/// The surface coordinates run from 0 to 1 along each edge and the normal
+/// faces `u × v`, so the winding decides which side is the front.
+pub struct Quad {
+    q: Point,
+    u: Vec3,
+    v: Vec3,
+    material: Arc<dyn Material>,
+    normal: Vec3,
+    /// plane offset along `normal`
+    d: f64,
+    /// `n / |n|²` with `n = u × v`, to project hit points onto the edges
+    w: Vec3,
+}
+
+impl Quad {
+    pub fn new(q: Point, u: Vec3, v: Vec3, material: Arc<dyn Material>) -> Self {
+        let n = cross(&u, &v);
+        let normal = unit_vector(&n);
+        Self {
+            q,
+            u,
+            v,
+            material,
+            normal,
+            d: dot(&normal, &q),
+            w: n / n.len_squared(),
+        }
+    }
+
+    pub fn obj(q: Point, u: Vec3, v: Vec3, material: Arc<dyn Material>) -> Box<dyn Hit> {
+        Box::new(Self::new(q, u, v, material))
+    }
+}
+
+impl Hit for Quad {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let denom = dot(&self.normal, &r.direction);
+        // parallel to the plane
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+        let t = (self.d - dot(&self.normal, &r.origin)) / denom;
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+
+        let p = r.at(t);
+        let planar = p - self.q;
+        let alpha = dot(&self.w, &cross(&planar, &self.v));
+        let beta = dot(&self.w, &cross(&self.u, &planar));
+        let unit = Interval::new(0.0, 1.0);
+        if !unit.contains(alpha) || !unit.contains(beta) {
+            return None;
+        }
+        Some(
+            HitRecord::with_normal(p, r, self.normal, t, Arc::clone(&self.material))
+                .with_uv(alpha, beta),
+        )
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let diagonal = Aabb::from_points(self.q, self.q + self.u + self.v);
+        let other = Aabb::from_points(self.q + self.u, self.q + self.v);
+        Aabb::union(&diagonal, &other)
+    }
+}
+
+/// The six outward facing quads of the axis aligned box with opposite
+/// corners `a` and `b`.
+pub fn cuboid(a: Point, b: Point, material: Arc<dyn Material>) -> Box<dyn Hit> {
+    let min = Vec3(a.0.min(b.0), a.1.min(b.1), a.2.min(b.2));
+    let max = Vec3(a.0.max(b.0), a.1.max(b.1), a.2.max(b.2));
+    let dx = Vec3(max.0 - min.0, 0., 0.);
+    let dy = Vec3(0., max.1 - min.1, 0.);
+    let dz = Vec3(0., 0., max.2 - min.2);
+
+    let mut sides = HittableList::new();
+    // front, right, back, left, top, bottom
+    let faces = [
+        (Vec3(min.0, min.1, max.2), dx, dy),
+        (Vec3(max.0, min.1, max.2), -dz, dy),
+        (Vec3(max.0, min.1, min.2), -dx, dy),
+        (Vec3(min.0, min.1, min.2), dz, dy),
+        (Vec3(min.0, max.1, max.2), dx, -dz),
+        (Vec3(min.0, min.1, min.2), dx, dz),
+    ];
+    for (q, u, v) in faces {
+        sides.add(Quad::obj(q, u, v, Arc::clone(&material)));
+    }
+    Bvh::obj(sides)
+}
+
+#[cfg(test)]
+#[test]
+fn test_quad_hit() {
+    use crate::material::Lambertian;
+
+    let material = Lambertian::obj(Vec3(0.5, 0.5, 0.5));
+    let quad = Quad::new(
+        Vec3(-1., -1., 0.),
+        Vec3(2., 0., 0.),
+        Vec3(0., 4., 0.),
+        material.clone(),
+    );
+    let ray_t = Interval::new(0.001, f64::MAX);
+    let toward = |x, y| Ray {
+        origin: Vec3(x, y, 5.),
+        direction: Vec3(0., 0., -1.),
+        time: 0.0,
+        wavelength: None,
+    };
+
+    let hit = quad.hit(&toward(0.5, 2.), &ray_t).expect("hits inside");
+    assert_eq!(hit.t, 5.0);
+    assert_eq!((hit.u, hit.v), (0.75, 0.75));
+    assert_eq!(hit.normal, Vec3(0., 0., 1.));
+    assert!(hit.front_face);
+    assert!(quad.hit(&toward(1.5, 0.), &ray_t).is_none());
+    assert!(quad.hit(&toward(0., 3.5), &ray_t).is_none());
+
+    let sides = cuboid(Vec3(1., 2., 3.), Vec3(0., 0., 0.), material);
+    let bbox = sides.bounding_box();
+    assert!(bbox.x.contains(0.) && bbox.x.contains(1.));
+    assert!(bbox.z.contains(0.) && bbox.z.contains(3.));
+    // every face normal points out of the box
+    let center = Vec3(0.5, 1., 1.5);
+    for dir in [
+        Vec3(1., 0., 0.),
+        Vec3(-1., 0., 0.),
+        Vec3(0., 1., 0.),
+        Vec3(0., -1., 0.),
+        Vec3(0., 0., 1.),
+        Vec3(0., 0., -1.),
+    ] {
+        let r = Ray {
+            origin: center + 10. * dir,
+            direction: -dir,
+            time: 0.0,
+            wavelength: None,
+        };
+        let hit = sides.hit(&r, &ray_t).expect("hits the box");
+        assert!(hit.front_face);
+        assert_eq!(hit.normal, dir);
+    }
+}
@@ -4,6 +4,16 @@ pub type Point3 = Vec3;
 pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
+    /// moment within the camera shutter the ray was sent at
+    pub time: f64,
+    /// in nanometers, for spectral rendering; `None` carries all of RGB
+    pub wavelength: Option<f64>,
+}
+
+impl Default for Ray {
+    fn default() -> Self {
+        Self::zero()
+    }
 }
 
 // constructors
@@ -16,10 +26,12 @@ impl Ray {
         Self {
             origin: Point3::zero(),
             direction: Vec3::zero(),
+            time: 0.0,
+            wavelength: None,
         }
     }
 
     pub fn at(&self, t: f64) -> Point3 {
-        &self.origin + &(&self.direction * t)
+        self.origin + self.direction * t
     }
 }
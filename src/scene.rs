@@ -0,0 +1,24 @@
+use crate::background::{Background, Gradient};
+use crate::hittable::Hit;
+
+/// Everything a camera needs to render: the objects and what lies beyond
+/// them.
+pub struct Scene<W: Hit> {
+    pub world: W,
+    pub background: Box<dyn Background>,
+}
+
+impl<W: Hit> Scene<W> {
+    /// Scene under the default sky gradient.
+    pub fn new(world: W) -> Self {
+        Self {
+            world,
+            background: Box::new(Gradient::sky()),
+        }
+    }
+
+    pub fn with_background(mut self, background: impl Background + 'static) -> Self {
+        self.background = Box::new(background);
+        self
+    }
+}
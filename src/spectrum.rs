@@ -0,0 +1,87 @@
+//! Wavelength sampling for spectral rendering.
+//!
+//! Each camera sample traces a single wavelength and its RGB result is
+//! weighted by what that wavelength looks like, so effects that depend on
+//! wavelength, like dispersion, come out in color while everything else
+//! averages back to the plain RGB render.
+
+use crate::vec3::{Color, Vec3};
+use std::sync::OnceLock;
+
+/// Visible range sampled, in nanometers.
+pub const WAVELENGTH_MIN: f64 = 380.0;
+pub const WAVELENGTH_MAX: f64 = 780.0;
+
+/// CIE 1931 color matching functions, from the multi-lobe Gaussian fit of
+/// Wyman, Sloan and Shirley (2013).
+pub fn cie_xyz(wavelength: f64) -> Vec3 {
+    let g = |mu: f64, below: f64, above: f64| {
+        let sigma = if wavelength < mu { below } else { above };
+        let t = (wavelength - mu) / sigma;
+        (-0.5 * t * t).exp()
+    };
+    Vec3(
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    )
+}
+
+/// CIE XYZ to linear sRGB (D65 white).
+pub fn xyz_to_rgb(xyz: &Vec3) -> Color {
+    Vec3(
+        3.2404542 * xyz.0 - 1.5371385 * xyz.1 - 0.4985314 * xyz.2,
+        -0.9692660 * xyz.0 + 1.8760108 * xyz.1 + 0.0415560 * xyz.2,
+        0.0556434 * xyz.0 - 0.2040259 * xyz.1 + 1.0572252 * xyz.2,
+    )
+}
+
+/// The in-gamut color of a single wavelength, before normalization.
+fn raw_color(wavelength: f64) -> Color {
+    let rgb = xyz_to_rgb(&cie_xyz(wavelength));
+    Vec3(rgb.0.max(0.0), rgb.1.max(0.0), rgb.2.max(0.0))
+}
+
+/// Per channel scale making the mean weight over the range exactly white.
+fn white_balance() -> Color {
+    static SCALE: OnceLock<Color> = OnceLock::new();
+    *SCALE.get_or_init(|| {
+        let steps = 4000;
+        let sum = (0..steps).fold(Color::zero(), |sum, i| {
+            let t = (i as f64 + 0.5) / steps as f64;
+            sum + raw_color(WAVELENGTH_MIN + t * (WAVELENGTH_MAX - WAVELENGTH_MIN))
+        });
+        let mean = sum / steps as f64;
+        Vec3(1.0 / mean.0, 1.0 / mean.1, 1.0 / mean.2)
+    })
+}
+
+/// Maps `u` in `[0, 1)` to a wavelength and the weight its radiance gets
+/// in the image. Weights average to white over uniform `u`.
+pub fn sample_wavelength(u: f64) -> (f64, Color) {
+    let wavelength = WAVELENGTH_MIN + u * (WAVELENGTH_MAX - WAVELENGTH_MIN);
+    (wavelength, raw_color(wavelength) * white_balance())
+}
+
+#[cfg(test)]
+#[test]
+fn test_spectrum() {
+    // the luminance curve peaks near 555nm
+    assert!((cie_xyz(555.0).1 - 1.0).abs() < 0.02);
+    assert!(cie_xyz(555.0).1 > cie_xyz(500.0).1 && cie_xyz(555.0).1 > cie_xyz(610.0).1);
+    // D65 white in XYZ is white in sRGB
+    let white = xyz_to_rgb(&Vec3(0.95047, 1.0, 1.08883));
+    assert!((white - Color::one()).len() < 1e-3);
+
+    let n = 1000;
+    let mean = (0..n).fold(Color::zero(), |sum, i| {
+        sum + sample_wavelength((i as f64 + 0.5) / n as f64).1
+    }) / n as f64;
+    assert!((mean - Color::one()).len() < 1e-2);
+    let (blue, weight) = sample_wavelength(0.2);
+    assert_eq!(blue, 460.0);
+    assert!(weight.2 > weight.0 && weight.2 > weight.1);
+    let (red, weight) = sample_wavelength(0.7);
+    assert_eq!(red, 660.0);
+    assert!(weight.0 > weight.1 && weight.0 > weight.2);
+}
@@ -1,31 +1,65 @@
+use super::aabb::Aabb;
 use super::hittable::{Hit, HitRecord};
 use super::interval::Interval;
 use super::material::Material;
 use super::ray::Ray;
-use super::vec3::{Point, dot};
-use std::rc::Rc;
+use super::vec3::{Point, Vec3, dot};
+use std::f64::consts::PI;
+use std::sync::Arc;
 pub struct Sphere {
+    /// center at the start of the motion
     pub center: Point,
     pub radius: f64,
-    pub material: Rc<dyn Material>,
+    pub material: Arc<dyn Material>,
+    /// distance the center travels over `motion_time`
+    pub motion: Vec3,
+    /// times the motion starts and ends, on the camera shutter's clock
+    pub motion_time: (f64, f64),
 }
 
 impl Sphere {
-    pub fn new(center: Point, radius: f64, material: Rc<dyn Material>) -> Self {
+    pub fn new(center: Point, radius: f64, material: Arc<dyn Material>) -> Self {
         Self {
             center,
             radius,
             material,
+            motion: Vec3::zero(),
+            motion_time: (0.0, 1.0),
         }
     }
-    pub fn obj(center: Point, radius: f64, material: Rc<dyn Material>) -> Box<dyn Hit> {
+    pub fn obj(center: Point, radius: f64, material: Arc<dyn Material>) -> Box<dyn Hit> {
         Box::new(Self::new(center, radius, material))
     }
+
+    /// Moves the sphere linearly from its center at `time0` to `center1` at
+    /// `time1`, in the same absolute times as the camera shutter. Outside
+    /// that span it stays at the nearer end.
+    pub fn with_motion(mut self, center1: Point, time0: f64, time1: f64) -> Self {
+        assert!(time0 < time1, "motion must end after it starts");
+        self.motion = center1 - self.center;
+        self.motion_time = (time0, time1);
+        self
+    }
+
+    pub fn center_at(&self, time: f64) -> Point {
+        let (time0, time1) = self.motion_time;
+        let s = ((time - time0) / (time1 - time0)).clamp(0.0, 1.0);
+        self.center + s * self.motion
+    }
+
+    /// Spherical coordinates of a point on the unit sphere: `u` runs around
+    /// the `y` axis starting from `-x`, `v` from the bottom pole to the top.
+    pub fn uv(p: &Point) -> (f64, f64) {
+        let theta = f64::acos(-p.1);
+        let phi = f64::atan2(-p.2, p.0) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
 }
 
 impl Hit for Sphere {
     fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
-        let oc = self.center - r.origin;
+        let center = self.center_at(r.time);
+        let oc = center - r.origin;
         let a = r.direction.len_squared();
         let h = dot(&r.direction, &oc);
         let c = oc.len_squared() - self.radius * self.radius;
@@ -56,10 +90,21 @@ impl Hit for Sphere {
             // using the bounded root return a HitRecord
             .map(|root| {
                 let p = r.at(root);
-                let normal = (p - self.center) / self.radius;
-                HitRecord::with_normal(p, r, normal, root, Rc::clone(&self.material))
+                let normal = (p - center) / self.radius;
+                let (u, v) = Self::uv(&normal);
+                HitRecord::with_normal(p, r, normal, root, Arc::clone(&self.material)).with_uv(u, v)
             })
     }
+
+    fn bounding_box(&self) -> Aabb {
+        let r = self.radius.abs();
+        let rvec = Vec3(r, r, r);
+        let end = self.center + self.motion;
+        Aabb::union(
+            &Aabb::from_points(self.center - rvec, self.center + rvec),
+            &Aabb::from_points(end - rvec, end + rvec),
+        )
+    }
 }
 
 #[macro_export]
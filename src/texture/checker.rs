@@ -0,0 +1,91 @@
+use super::Texture;
+use crate::vec3::{Color, Point};
+use std::sync::Arc;
+
+/// Alternates between two textures in a 3D grid of `scale` sized cells, so
+/// the pattern cuts through objects rather than wrapping around them.
+pub struct Checker {
+    inv_scale: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl Checker {
+    pub fn new(
+        scale: f64,
+        even: impl Into<Arc<dyn Texture>>,
+        odd: impl Into<Arc<dyn Texture>>,
+    ) -> Self {
+        Self {
+            inv_scale: 1.0 / scale,
+            even: even.into(),
+            odd: odd.into(),
+        }
+    }
+
+    pub fn obj(
+        scale: f64,
+        even: impl Into<Arc<dyn Texture>>,
+        odd: impl Into<Arc<dyn Texture>>,
+    ) -> Arc<dyn Texture> {
+        Arc::new(Self::new(scale, even, odd))
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, p: &Point) -> Color {
+        let cell = (self.inv_scale * p.0).floor() as i64
+            + (self.inv_scale * p.1).floor() as i64
+            + (self.inv_scale * p.2).floor() as i64;
+        if cell % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+/// Alternates between two textures over a `u_count` by `v_count` grid of
+/// surface coordinates, so the pattern follows the surface.
+pub struct UvChecker {
+    u_count: f64,
+    v_count: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl UvChecker {
+    pub fn new(
+        u_count: f64,
+        v_count: f64,
+        even: impl Into<Arc<dyn Texture>>,
+        odd: impl Into<Arc<dyn Texture>>,
+    ) -> Self {
+        Self {
+            u_count,
+            v_count,
+            even: even.into(),
+            odd: odd.into(),
+        }
+    }
+
+    pub fn obj(
+        u_count: f64,
+        v_count: f64,
+        even: impl Into<Arc<dyn Texture>>,
+        odd: impl Into<Arc<dyn Texture>>,
+    ) -> Arc<dyn Texture> {
+        Arc::new(Self::new(u_count, v_count, even, odd))
+    }
+}
+
+impl Texture for UvChecker {
+    fn value(&self, u: f64, v: f64, p: &Point) -> Color {
+        let cell = (u * self.u_count).floor() as i64 + (v * self.v_count).floor() as i64;
+        if cell % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use super::Texture;
+use crate::film::Film;
+use crate::image;
+use crate::vec3::{Color, Point};
+use std::path::Path;
+use std::sync::Arc;
+
+/// How lookups between texel centers are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+/// How surface coordinates outside `[0, 1]` map onto the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressMode {
+    /// tile the image
+    Wrap,
+    /// repeat the edge texels
+    Clamp,
+    /// tile the image, flipping every other copy
+    Mirror,
+}
+
+impl AddressMode {
+    /// texel index for a possibly out of range coordinate along an axis of
+    /// `n` texels
+    fn resolve(&self, i: i64, n: usize) -> usize {
+        let n = n as i64;
+        let i = match self {
+            Self::Wrap => i.rem_euclid(n),
+            Self::Clamp => i.clamp(0, n - 1),
+            Self::Mirror => {
+                let m = i.rem_euclid(2 * n);
+                if m >= n { 2 * n - 1 - m } else { m }
+            }
+        };
+        i as usize
+    }
+}
+
+/// Maps an image over `[0, 1]` surface coordinates, with `v = 1` at the top
+/// row. Defaults to bilinear filtering and wrapping.
+pub struct ImageTexture {
+    image: Film,
+    filter: Filter,
+    address: AddressMode,
+}
+
+impl ImageTexture {
+    /// `image` holds linear colors, like a rendered `Film`.
+    pub fn new(image: Film) -> Self {
+        assert!(
+            image.width() > 0 && image.height() > 0,
+            "texture image is empty"
+        );
+        Self {
+            image,
+            filter: Filter::Bilinear,
+            address: AddressMode::Wrap,
+        }
+    }
+
+    pub fn obj(image: Film) -> Arc<dyn Texture> {
+        Arc::new(Self::new(image))
+    }
+
+    /// Decodes a PNG, JPEG, PPM, Radiance HDR or OpenEXR image. 8 and 16
+    /// bit images are converted from sRGB to linear.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, std::io::Error> {
+        Ok(Self::new(image::load(path)?))
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_address_mode(mut self, address: AddressMode) -> Self {
+        self.address = address;
+        self
+    }
+
+    fn texel(&self, i: i64, j: i64) -> Color {
+        let i = self.address.resolve(i, self.image.width());
+        let j = self.address.resolve(j, self.image.height());
+        self.image.get(i, j)
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _: &Point) -> Color {
+        // continuous texel coordinates, row 0 at the top
+        let x = u * self.image.width() as f64;
+        let y = (1.0 - v) * self.image.height() as f64;
+        match self.filter {
+            Filter::Nearest => self.texel(x.floor() as i64, y.floor() as i64),
+            Filter::Bilinear => {
+                // blend the four texels whose centers surround the lookup
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (i, j) = (x.floor(), y.floor());
+                let (fx, fy) = (x - i, y - j);
+                let (i, j) = (i as i64, j as i64);
+                let top = (1.0 - fx) * self.texel(i, j) + fx * self.texel(i + 1, j);
+                let bottom = (1.0 - fx) * self.texel(i, j + 1) + fx * self.texel(i + 1, j + 1);
+                (1.0 - fy) * top + fy * bottom
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_image_texture_addressing() {
+    use crate::vec3::Vec3;
+
+    let mut image = Film::new(2, 1);
+    image.set(0, 0, Vec3(0., 0., 0.));
+    image.set(1, 0, Vec3(1., 1., 1.));
+    let p = Vec3::zero();
+    let nearest = |address| {
+        ImageTexture::new(image.clone())
+            .with_filter(Filter::Nearest)
+            .with_address_mode(address)
+    };
+    assert_eq!(nearest(AddressMode::Wrap).value(1.25, 0.5, &p).0, 0.0);
+    assert_eq!(nearest(AddressMode::Clamp).value(1.25, 0.5, &p).0, 1.0);
+    assert_eq!(nearest(AddressMode::Mirror).value(1.25, 0.5, &p).0, 1.0);
+    assert_eq!(nearest(AddressMode::Mirror).value(-0.25, 0.5, &p).0, 0.0);
+
+    let bilinear = ImageTexture::new(image.clone()).with_address_mode(AddressMode::Clamp);
+    // halfway between the two texel centers
+    assert_eq!(bilinear.value(0.5, 0.5, &p).0, 0.5);
+    assert_eq!(bilinear.value(0.0, 0.5, &p).0, 0.0);
+    assert_eq!(bilinear.value(0.375, 0.5, &p).0, 0.25);
+}
@@ -0,0 +1,25 @@
+mod checker;
+mod image_texture;
+mod noise;
+mod solid_color;
+
+pub use checker::{Checker, UvChecker};
+pub use image_texture::{AddressMode, Filter, ImageTexture};
+pub use noise::{Marble, Turbulence, Wood};
+pub use solid_color::SolidColor;
+
+use crate::vec3::{Color, Point};
+use std::sync::Arc;
+
+/// Color that varies over a surface, looked up by the hit's surface
+/// coordinates `u`, `v` and its position `p`.
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, p: &Point) -> Color;
+}
+
+/// Lets materials take a flat color wherever they take a texture.
+impl From<Color> for Arc<dyn Texture> {
+    fn from(color: Color) -> Self {
+        SolidColor::obj(color)
+    }
+}
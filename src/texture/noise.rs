@@ -0,0 +1,127 @@
+use super::Texture;
+use crate::perlin::Perlin;
+use crate::vec3::{Color, Point, Vec3};
+use std::sync::Arc;
+
+fn mix(a: &Color, b: &Color, t: f64) -> Color {
+    (1.0 - t) * a + t * b
+}
+
+/// Grayscale turbulence, like smoke or stained plaster.
+pub struct Turbulence {
+    noise: Perlin,
+    scale: f64,
+    octaves: u32,
+    color: Color,
+}
+
+impl Turbulence {
+    pub fn new(seed: u64, scale: f64) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            octaves: 7,
+            color: Color::one(),
+        }
+    }
+
+    pub fn obj(seed: u64, scale: f64) -> Arc<dyn Texture> {
+        Arc::new(Self::new(seed, scale))
+    }
+
+    pub fn with_octaves(mut self, octaves: u32) -> Self {
+        self.octaves = octaves;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+impl Texture for Turbulence {
+    fn value(&self, _: f64, _: f64, p: &Point) -> Color {
+        let t = self.noise.turbulence(&(self.scale * p), self.octaves);
+        t.min(1.0) * self.color
+    }
+}
+
+/// Veins along `z`, a sine wave whose phase is pushed around by
+/// turbulence.
+pub struct Marble {
+    noise: Perlin,
+    scale: f64,
+    dark: Color,
+    light: Color,
+}
+
+impl Marble {
+    pub fn new(seed: u64, scale: f64) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            dark: Vec3(0.1, 0.1, 0.12),
+            light: Vec3(0.9, 0.9, 0.88),
+        }
+    }
+
+    pub fn obj(seed: u64, scale: f64) -> Arc<dyn Texture> {
+        Arc::new(Self::new(seed, scale))
+    }
+
+    pub fn with_colors(mut self, dark: Color, light: Color) -> Self {
+        self.dark = dark;
+        self.light = light;
+        self
+    }
+}
+
+impl Texture for Marble {
+    fn value(&self, _: f64, _: f64, p: &Point) -> Color {
+        let phase = self.scale * p.2 + 10.0 * self.noise.turbulence(p, 7);
+        mix(&self.dark, &self.light, 0.5 * (1.0 + phase.sin()))
+    }
+}
+
+/// Growth rings around the `y` axis, `rings` per unit distance, warped by
+/// fractal noise.
+pub struct Wood {
+    noise: Perlin,
+    rings: f64,
+    dark: Color,
+    light: Color,
+}
+
+impl Wood {
+    pub fn new(seed: u64, rings: f64) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            rings,
+            dark: Vec3(0.35, 0.18, 0.07),
+            light: Vec3(0.72, 0.5, 0.28),
+        }
+    }
+
+    pub fn obj(seed: u64, rings: f64) -> Arc<dyn Texture> {
+        Arc::new(Self::new(seed, rings))
+    }
+
+    pub fn with_colors(mut self, dark: Color, light: Color) -> Self {
+        self.dark = dark;
+        self.light = light;
+        self
+    }
+}
+
+impl Texture for Wood {
+    fn value(&self, _: f64, _: f64, p: &Point) -> Color {
+        let radius = (p.0 * p.0 + p.2 * p.2).sqrt();
+        // low frequency noise, stretched along the grain, wobbles the rings
+        let grain = Vec3(1.5 * p.0, 0.3 * p.1, 1.5 * p.2);
+        let r = self.rings * radius + 1.5 * self.noise.fbm(&grain, 4, 2.0, 0.5);
+        let ring = r - r.floor();
+        // sharp late-wood edge at the end of each ring
+        mix(&self.light, &self.dark, ring.powi(3))
+    }
+}
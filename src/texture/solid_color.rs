@@ -0,0 +1,23 @@
+use super::Texture;
+use crate::vec3::{Color, Point};
+use std::sync::Arc;
+
+pub struct SolidColor {
+    albedo: Color,
+}
+
+impl SolidColor {
+    pub fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+
+    pub fn obj(albedo: Color) -> Arc<dyn Texture> {
+        Arc::new(Self::new(albedo))
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _: f64, _: f64, _: &Point) -> Color {
+        self.albedo
+    }
+}
@@ -0,0 +1,248 @@
+use super::aabb::Aabb;
+use super::hittable::{Hit, HitRecord};
+use super::interval::Interval;
+use super::material::Material;
+use super::ray::Ray;
+use super::vec3::{Point, Vec3, cross, dot, unit_vector};
+use std::sync::Arc;
+
+/// Watertight ray/triangle test (Woop, Benthin and Wald 2013).
+///
+/// The vertices are moved into a space where the ray starts at the origin
+/// and runs along `+z`, and the hit is decided from the signs of 2D edge
+/// functions. Neighbouring triangles evaluate a shared edge identically,
+/// so rays cannot slip between them. Returns `t` and the barycentric
+/// weights of the three vertices.
+pub(crate) fn intersect(p: [&Point; 3], r: &Ray, ray_t: &Interval) -> Option<(f64, [f64; 3])> {
+    let d = &r.direction;
+    // permute so the largest direction component becomes z, keeping the
+    // handedness so edge signs stay meaningful
+    let kz = if d.0.abs() > d.1.abs() {
+        if d.0.abs() > d.2.abs() { 0 } else { 2 }
+    } else if d.1.abs() > d.2.abs() {
+        1
+    } else {
+        2
+    };
+    let mut kx = (kz + 1) % 3;
+    let mut ky = (kx + 1) % 3;
+    if d.axis(kz) < 0.0 {
+        std::mem::swap(&mut kx, &mut ky);
+    }
+
+    // shear so the ray direction becomes (0, 0, 1)
+    let dz = d.axis(kz);
+    let sx = -d.axis(kx) / dz;
+    let sy = -d.axis(ky) / dz;
+    let sz = 1.0 / dz;
+    let v = p.map(|p| {
+        let p = p - r.origin;
+        let z = p.axis(kz);
+        Vec3(p.axis(kx) + sx * z, p.axis(ky) + sy * z, sz * z)
+    });
+
+    let e = [
+        v[1].0 * v[2].1 - v[1].1 * v[2].0,
+        v[2].0 * v[0].1 - v[2].1 * v[0].0,
+        v[0].0 * v[1].1 - v[0].1 * v[1].0,
+    ];
+    // the ray passes on different sides of two edges
+    if e.iter().any(|&e| e < 0.0) && e.iter().any(|&e| e > 0.0) {
+        return None;
+    }
+    let det = e[0] + e[1] + e[2];
+    if det == 0.0 {
+        return None;
+    }
+    let t = (e[0] * v[0].2 + e[1] * v[1].2 + e[2] * v[2].2) / det;
+    if !ray_t.surrounds(t) {
+        return None;
+    }
+    Some((t, e.map(|e| e / det)))
+}
+
+/// Fills in a hit from barycentric weights `b`. When vertex normals are
+/// given the interpolated normal is used for shading, while the facing is
+/// always decided by the geometric normal from the winding.
+pub(crate) fn hit_record(
+    p: [&Point; 3],
+    normals: Option<[&Vec3; 3]>,
+    uvs: [(f64, f64); 3],
+    t: f64,
+    b: [f64; 3],
+    r: &Ray,
+    material: Arc<dyn Material>,
+) -> HitRecord {
+    let point = b[0] * p[0] + b[1] * p[1] + b[2] * p[2];
+    let geometric = unit_vector(&cross(&(p[1] - p[0]), &(p[2] - p[0])));
+    let u = b[0] * uvs[0].0 + b[1] * uvs[1].0 + b[2] * uvs[2].0;
+    let v = b[0] * uvs[0].1 + b[1] * uvs[1].1 + b[2] * uvs[2].1;
+    let mut rec = HitRecord::with_normal(point, r, geometric, t, material).with_uv(u, v);
+
+    if let Some(n) = normals {
+        let shading = b[0] * n[0] + b[1] * n[1] + b[2] * n[2];
+        if shading.len_squared() > 0.0 {
+            let shading = unit_vector(&shading);
+            // keep the shading normal on the side the hit was seen from
+            rec.normal = if dot(&shading, &rec.normal) < 0.0 {
+                -shading
+            } else {
+                shading
+            };
+        }
+    }
+    rec
+}
+
+/// Single triangle with optional per-vertex normals and surface coordinates.
+///
+/// The front face is the side from which `a`, `b`, `c` appear counter
+/// clockwise. Without explicit coordinates `(u, v)` are `(0, 0)`, `(1, 0)`
+/// and `(0, 1)` at the three vertices.
+pub struct Triangle {
+    vertices: [Point; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: [(f64, f64); 3],
+    material: Arc<dyn Material>,
+}
+
+impl Triangle {
+    pub fn new(a: Point, b: Point, c: Point, material: Arc<dyn Material>) -> Self {
+        Self {
+            vertices: [a, b, c],
+            normals: None,
+            uvs: [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)],
+            material,
+        }
+    }
+
+    pub fn obj(a: Point, b: Point, c: Point, material: Arc<dyn Material>) -> Box<dyn Hit> {
+        Box::new(Self::new(a, b, c, material))
+    }
+
+    /// Vertex normals for smooth shading; they need not be unit length.
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Self {
+        self.normals = Some(normals);
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: [(f64, f64); 3]) -> Self {
+        self.uvs = uvs;
+        self
+    }
+}
+
+impl Hit for Triangle {
+    fn hit(&self, r: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+        let p = self.vertices.each_ref();
+        let (t, b) = intersect(p, r, ray_t)?;
+        let normals = self.normals.as_ref().map(|n| n.each_ref());
+        Some(hit_record(
+            p,
+            normals,
+            self.uvs,
+            t,
+            b,
+            r,
+            Arc::clone(&self.material),
+        ))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let bbox = self
+            .vertices
+            .iter()
+            .fold(Aabb::empty(), |bbox, &p| bbox.enclose(p));
+        // rebuild through `new` so flat triangles get padded
+        Aabb::new(bbox.x, bbox.y, bbox.z)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_triangle_hit() {
+    use crate::material::Lambertian;
+
+    let material = Lambertian::obj(Vec3(0.5, 0.5, 0.5));
+    let tri = Triangle::new(
+        Vec3(0., 0., 0.),
+        Vec3(2., 0., 0.),
+        Vec3(0., 2., 0.),
+        material,
+    )
+    .with_uvs([(0., 0.), (1., 0.), (1., 1.)]);
+    let ray_t = Interval::new(0.001, f64::MAX);
+    let toward = |x, y| Ray {
+        origin: Vec3(x, y, 3.),
+        direction: Vec3(0., 0., -1.),
+        time: 0.0,
+        wavelength: None,
+    };
+
+    let hit = tri.hit(&toward(0.5, 0.5), &ray_t).expect("hits inside");
+    assert_eq!(hit.t, 3.0);
+    assert_eq!(hit.p, Vec3(0.5, 0.5, 0.));
+    assert_eq!((hit.u, hit.v), (0.5, 0.25));
+    assert!(hit.front_face);
+    assert_eq!(hit.normal, Vec3(0., 0., 1.));
+    assert!(tri.hit(&toward(1.5, 1.5), &ray_t).is_none());
+    assert!(tri.hit(&toward(-0.1, 0.5), &ray_t).is_none());
+
+    // smooth shading bends the normal but the facing follows the winding
+    let smooth = Triangle::new(
+        Vec3(0., 0., 0.),
+        Vec3(2., 0., 0.),
+        Vec3(0., 2., 0.),
+        Lambertian::obj(Vec3(0.5, 0.5, 0.5)),
+    )
+    .with_normals([Vec3(1., 0., 1.), Vec3(1., 0., 1.), Vec3(1., 0., 1.)]);
+    let below = Ray {
+        origin: Vec3(0.5, 0.5, -3.),
+        direction: Vec3(0., 0., 1.),
+        time: 0.0,
+        wavelength: None,
+    };
+    let hit = smooth.hit(&below, &ray_t).expect("hits the back");
+    assert!(!hit.front_face);
+    assert!(hit.normal.2 < 0.0 && (hit.normal.len() - 1.0).abs() < 1e-12);
+}
+
+#[cfg(test)]
+#[test]
+fn test_triangle_watertight() {
+    use crate::material::Lambertian;
+
+    // a fan of thin triangles around the origin; rays aimed exactly at the
+    // shared edges and the shared vertex must never fall through
+    let material = Lambertian::obj(Vec3(0.5, 0.5, 0.5));
+    let n = 7;
+    let rim = |i: usize| {
+        let a = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+        Vec3(a.cos(), a.sin(), 0.3 * a.sin())
+    };
+    let fan: Vec<Triangle> = (0..n)
+        .map(|i| Triangle::new(Vec3::zero(), rim(i), rim((i + 1) % n), material.clone()))
+        .collect();
+    let ray_t = Interval::new(0.001, f64::MAX);
+    for i in 0..n {
+        for s in [0.0, 0.1, 0.37, 0.5, 0.9] {
+            let target = s * rim(i);
+            for origin in [
+                Vec3(0.3, -0.2, 5.),
+                Vec3(-4., 1., -3.),
+                Vec3(0.01, 0.02, 9.),
+            ] {
+                let r = Ray {
+                    origin,
+                    direction: target - origin,
+                    time: 0.0,
+                    wavelength: None,
+                };
+                assert!(
+                    fan.iter().any(|t| t.hit(&r, &ray_t).is_some()),
+                    "ray through {target} leaked"
+                );
+            }
+        }
+    }
+}
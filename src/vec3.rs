@@ -27,6 +27,15 @@ impl Vec3 {
         self.2
     }
 
+    /// component by index: 0 => x, 1 => y, 2 => z
+    pub fn axis(&self, n: usize) -> f64 {
+        match n {
+            0 => self.0,
+            1 => self.1,
+            _ => self.2,
+        }
+    }
+
     pub fn len(&self) -> f64 {
         f64::sqrt(self.len_squared())
     }